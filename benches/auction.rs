@@ -0,0 +1,93 @@
+//! Auction circuits over different no. of bidders and bid bits, and each
+//! circuit variant at a fixed size.
+//!
+//! Bids are sampled from a fixed seed, thus every run benchmarks the same
+//! auctions. Run with `cargo bench --bench auction`
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fhe_auctions::{Auction, Direction, MultiUnit, MultiUnitPricing, Pricing, TieBreak, TopK};
+use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+use common::encrypt_bids;
+
+mod common;
+
+fn first_price(c: &mut Criterion) {
+    let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+
+    let mut group = c.benchmark_group("first_price");
+    for bid_bits in [8, 16, 32, 64] {
+        for bidders in [2, 8, 32] {
+            let bids = encrypt_bids(&client_key, bidders, bid_bits);
+            group.bench_with_input(
+                BenchmarkId::new(format!("{bid_bits}_bits"), bidders),
+                &bids,
+                |b, bids| b.iter(|| Auction::new(&server_key, bid_bits).run(bids).unwrap()),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn variants(c: &mut Criterion) {
+    let bidders = 8;
+    let bid_bits = 16;
+    let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+    let bids = encrypt_bids(&client_key, bidders, bid_bits);
+    let reserve = encrypt_bids(&client_key, 1, bid_bits).remove(0);
+
+    let mut group = c.benchmark_group(format!("variants_{bidders}x{bid_bits}"));
+    let auctions = [
+        ("first_price", Auction::new(&server_key, bid_bits)),
+        (
+            "second_price",
+            Auction::new(&server_key, bid_bits).with_pricing(Pricing::SecondPrice),
+        ),
+        (
+            "reverse",
+            Auction::new(&server_key, bid_bits).with_direction(Direction::Reverse),
+        ),
+        (
+            "reserve",
+            Auction::new(&server_key, bid_bits).with_reserve(&reserve),
+        ),
+        (
+            "tie_break",
+            Auction::new(&server_key, bid_bits).with_tie_break(TieBreak::LowestIndex),
+        ),
+    ];
+    for (name, auction) in auctions.iter() {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| auction.run(&bids).unwrap())
+        });
+    }
+
+    group.bench_function(BenchmarkId::from_parameter("early_decrypt"), |b| {
+        let mut oracle = client_key.clone();
+        let auction = Auction::new(&server_key, bid_bits);
+        b.iter(|| auction.run_early_decrypt(&bids, &mut oracle).unwrap())
+    });
+
+    group.bench_function(BenchmarkId::from_parameter("top_3"), |b| {
+        let top_k = TopK::new(&server_key, bid_bits, 3);
+        b.iter(|| top_k.run(&bids).unwrap())
+    });
+
+    for (name, pricing) in [
+        ("multi_unit_3_uniform", MultiUnitPricing::Uniform),
+        ("multi_unit_3_pay_as_bid", MultiUnitPricing::PayAsBid),
+    ] {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            let multi_unit = MultiUnit::new(&server_key, bid_bits, 3).with_pricing(pricing);
+            b.iter(|| multi_unit.run(&bids).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // circuits take seconds, thus use the fewest samples criterion allows
+    config = Criterion::default().sample_size(10);
+    targets = first_price, variants
+}
+criterion_main!(benches);
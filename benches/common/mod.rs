@@ -0,0 +1,22 @@
+//! Helpers shared by all benchmarks
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tfhe::gadget::{ciphertext::Ciphertext, client_key::ClientKey};
+
+/// Encrypts `bidders` random bids of `bid_bits` bits from MSB to LSB. Bids are
+/// sampled from a seed fixed per size, thus every run benchmarks the same
+/// auctions.
+pub fn encrypt_bids(
+    client_key: &ClientKey,
+    bidders: usize,
+    bid_bits: usize,
+) -> Vec<Vec<Ciphertext>> {
+    let mut rng = StdRng::seed_from_u64((bidders * 1000 + bid_bits) as u64);
+    (0..bidders)
+        .map(|_| {
+            let bid_amount = rng.gen::<u64>();
+            (0..bid_bits)
+                .map(|i| client_key.encrypt((bid_amount >> (bid_bits - 1 - i)) & 1 != 0))
+                .collect()
+        })
+        .collect()
+}
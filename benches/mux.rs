@@ -0,0 +1,54 @@
+//! Compares the three gate multiplexer `(b & s) | (!b & w)` against the single
+//! bootstrap multiplexer in 7-encoding, both for a single multiplexer and for
+//! the auction circuit on 50 bidders with 64 bit bids.
+//!
+//! Run with `cargo bench --bench mux`
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fhe_auctions::{Auction, CircuitBuilder, Multiplexer};
+use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+use common::encrypt_bids;
+
+mod common;
+
+fn multiplexers(c: &mut Criterion) {
+    let bidders = 50;
+    let bid_bits = 64;
+
+    let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+    let bids = encrypt_bids(&client_key, bidders, bid_bits);
+
+    let b = client_key.encrypt(true);
+    let x = client_key.encrypt(false);
+    let y = client_key.encrypt(true);
+
+    let mut group = c.benchmark_group("single_mux");
+    group.bench_function(BenchmarkId::from_parameter("ThreeGate"), |bench| {
+        bench.iter(|| {
+            let c0 = server_key.and(&b, &x).unwrap();
+            let c1 = server_key.and(&server_key.not(&b), &y).unwrap();
+            server_key.or(&c0, &c1).unwrap()
+        })
+    });
+    group.bench_function(BenchmarkId::from_parameter("SevenEncoding"), |bench| {
+        bench.iter(|| server_key.mux(&b, &x, &y).unwrap())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group(format!("auction_{bidders}x{bid_bits}"));
+    for multiplexer in [Multiplexer::ThreeGate, Multiplexer::SevenEncoding] {
+        let auction = Auction::new(&server_key, bid_bits).with_multiplexer(multiplexer);
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{multiplexer:?}")),
+            |bench| bench.iter(|| auction.run(&bids).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = multiplexers
+}
+criterion_main!(benches);
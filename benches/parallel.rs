@@ -0,0 +1,43 @@
+//! Compares auction circuit on 50 bidders with 64 bit bids across no. of
+//! threads, doubling up to the available parallelism.
+//!
+//! Run with `cargo bench --features parallel --bench parallel`
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use fhe_auctions::{Auction, CircuitBuilder};
+use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+use common::encrypt_bids;
+
+mod common;
+
+fn threads(c: &mut Criterion) {
+    let bidders = 50;
+    let bid_bits = 64;
+
+    let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+    let bids = encrypt_bids(&client_key, bidders, bid_bits);
+
+    let max_threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let mut threads = vec![1];
+    while threads.last() < Some(&max_threads) {
+        threads.push((threads.last().unwrap() * 2).min(max_threads));
+    }
+
+    let mut group = c.benchmark_group(format!("threads_{bidders}x{bid_bits}"));
+    for t in threads {
+        let auction = Auction::new(&server_key, bid_bits).with_threads(t);
+        group.bench_function(BenchmarkId::from_parameter(t), |b| {
+            b.iter(|| auction.run(&bids).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = threads
+}
+criterion_main!(benches);
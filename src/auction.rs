@@ -0,0 +1,448 @@
+use tfhe::gadget::{ciphertext::Ciphertext, server_key::ServerKey};
+
+use crate::{
+    auction_circuit, complement,
+    cost::CountingEngine,
+    early_decrypt::{early_decrypt_circuit, DecryptionOracle, EarlyDecryptResult},
+    mask,
+    parallel::install,
+    reserve::{greater_or_equal, max},
+    tie_break::{tie_break, TieBreak},
+    validate_bids,
+    vickrey::{second_price_circuit, SecondPriceOutput},
+    AuctionError, BidBit, BooleanEngine, CircuitBuilder, CircuitOptions, PlaintextEngine,
+};
+
+/// Sealed-bid auction over encrypted bids.
+///
+/// Each bid must be `bid_bits` encrypted bits ordered from MSB to LSB.
+pub struct Auction<'a, E: BooleanEngine = ServerKey> {
+    server_key: &'a E,
+    bid_bits: usize,
+    pricing: Pricing,
+    direction: Direction,
+    reserve: Option<&'a [E::Ciphertext]>,
+    tie_break: Option<TieBreak>,
+    options: CircuitOptions,
+}
+
+/// Amount paid by the winner
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pricing {
+    /// Winner pays the best bid
+    #[default]
+    FirstPrice,
+    /// Winner pays the second best bid (Vickrey). If the best bid is tied
+    /// winner pays the best bid.
+    ///
+    /// With a single bidder there is no second bid, thus the winner pays the
+    /// worst possible bid: 0 in forward auctions and `2^k - 1` for `k` bid bits
+    /// in reverse auctions. Set a reserve price to bound the amount.
+    SecondPrice,
+}
+
+/// Which bid wins the auction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Highest bid wins
+    #[default]
+    Forward,
+    /// Lowest bid wins (procurement auction)
+    Reverse,
+}
+
+impl Direction {
+    /// Maps `bits` such that the winning bid is the highest.
+    ///
+    /// Complementing every bit maps x to 2^k - 1 - x, thus for reverse auctions
+    /// the lowest bid becomes the highest. Since complement is an involution it
+    /// also maps amounts back.
+    pub(crate) fn orient<E: BooleanEngine>(
+        self,
+        server_key: &E,
+        bits: &[E::Ciphertext],
+    ) -> Vec<E::Ciphertext> {
+        match self {
+            Direction::Forward => bits.to_vec(),
+            Direction::Reverse => complement(server_key, bits),
+        }
+    }
+}
+
+/// Encrypted output of an auction
+#[derive(Clone)]
+pub struct AuctionResult<C = Ciphertext> {
+    /// `winners[j]` encrypts 1 iff j^th bidder placed the winning bid. With tie
+    /// break at most one entry encrypts 1.
+    pub winners: Vec<C>,
+    /// Amount paid by the winner, bits from MSB to LSB
+    pub amount: Vec<C>,
+    /// Encrypts 1 iff the winning bid meets the reserve price. Set only when
+    /// auction has a reserve price.
+    pub reserve_met: Option<C>,
+}
+
+impl<'a, E: BooleanEngine> CircuitBuilder for Auction<'a, E> {
+    fn options(&self) -> &CircuitOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut CircuitOptions {
+        &mut self.options
+    }
+
+    fn count_gates(
+        &self,
+        engine: &CountingEngine<PlaintextEngine>,
+        bidder_count: usize,
+        options: CircuitOptions,
+    ) -> Result<(), AuctionError> {
+        let reserve = self
+            .reserve
+            .map(|_| vec![engine.trivial(false); self.bid_bits]);
+        let auction = Auction {
+            server_key: engine,
+            bid_bits: self.bid_bits,
+            pricing: self.pricing,
+            direction: self.direction,
+            reserve: reserve.as_deref(),
+            tie_break: self.tie_break.clone(),
+            options,
+        };
+        auction.run(&vec![
+            vec![engine.trivial(false); self.bid_bits];
+            bidder_count
+        ])?;
+        Ok(())
+    }
+}
+
+impl<'a, E: BooleanEngine> Auction<'a, E> {
+    pub fn new(server_key: &'a E, bid_bits: usize) -> Self {
+        Auction {
+            server_key,
+            bid_bits,
+            pricing: Pricing::default(),
+            direction: Direction::default(),
+            reserve: None,
+            tie_break: None,
+            options: CircuitOptions::default(),
+        }
+    }
+
+    pub fn with_pricing(mut self, pricing: Pricing) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    /// Sets encrypted reserve price, `bid_bits` bits from MSB to LSB.
+    ///
+    /// For forward auctions the highest bid must be at least the reserve, for
+    /// reverse auctions the lowest bid must be at most the reserve. If reserve is
+    /// not met winner vector and amount are zeroed. With second price the winner
+    /// pays at least (resp. at most) the reserve.
+    pub fn with_reserve(mut self, reserve: &'a [E::Ciphertext]) -> Self {
+        self.reserve = Some(reserve);
+        self
+    }
+
+    /// Reduces tied winners to a single winner as per `policy`
+    pub fn with_tie_break(mut self, policy: TieBreak) -> Self {
+        self.tie_break = Some(policy);
+        self
+    }
+
+    pub fn bid_bits(&self) -> usize {
+        self.bid_bits
+    }
+
+    /// Runs auction circuit over `bids`, where `bids[j]` is bid of j^th bidder
+    pub fn run(
+        &self,
+        bids: &[Vec<E::Ciphertext>],
+    ) -> Result<AuctionResult<E::Ciphertext>, AuctionError> {
+        install(self.options.threads, || self.run_circuit(bids))
+    }
+
+    /// Runs auction with early decryption, where `oracle` decrypts the winning
+    /// amount bit by bit during the auction. Much faster than [Auction::run] but
+    /// requires an interactive decryptor and reveals the winning amount to it.
+    ///
+    /// Only first price auctions without reserve price are supported, since
+    /// the winning amount must be revealable.
+    pub fn run_early_decrypt<O: DecryptionOracle<E::Ciphertext> + Send>(
+        &self,
+        bids: &[Vec<E::Ciphertext>],
+        oracle: &mut O,
+    ) -> Result<EarlyDecryptResult<E::Ciphertext>, AuctionError> {
+        if self.pricing != Pricing::FirstPrice {
+            return Err(AuctionError::Unsupported(
+                "early decryption requires first price",
+            ));
+        }
+        if self.reserve.is_some() {
+            return Err(AuctionError::Unsupported(
+                "early decryption does not support reserve price",
+            ));
+        }
+
+        install(self.options.threads, || {
+            validate_bids(bids, self.bid_bits, bids.len())?;
+            let bids = bids
+                .iter()
+                .map(|bid| self.direction.orient(self.server_key, bid))
+                .collect::<Vec<_>>();
+
+            let (mut winners, mut amount) =
+                early_decrypt_circuit(self.server_key, &bids, self.bid_bits, oracle, self.options)?;
+
+            if let Some(policy) = &self.tie_break {
+                winners = tie_break(self.server_key, &winners, policy)?;
+            }
+            if self.direction == Direction::Reverse {
+                amount.iter_mut().for_each(|bit| *bit = !*bit);
+            }
+
+            Ok(EarlyDecryptResult { winners, amount })
+        })
+    }
+
+    fn run_circuit(
+        &self,
+        bids: &[Vec<E::Ciphertext>],
+    ) -> Result<AuctionResult<E::Ciphertext>, AuctionError> {
+        // Bids are validated before NOT hides trivial or placeholder ciphertexts
+        validate_bids(bids, self.bid_bits, bids.len())?;
+        if let Some(reserve) = self.reserve {
+            if reserve.len() != self.bid_bits {
+                return Err(AuctionError::ReserveLength {
+                    expected: self.bid_bits,
+                    found: reserve.len(),
+                });
+            }
+            reserve.iter().enumerate().try_for_each(|(bit, ct)| {
+                ct.validate(0, bit)
+                    .map_err(|_| AuctionError::ReserveBit { bit })
+            })?;
+        }
+
+        // Circuits below always find the highest
+        let bids = bids
+            .iter()
+            .map(|bid| self.direction.orient(self.server_key, bid))
+            .collect::<Vec<_>>();
+
+        let (mut winners, highest, mut amount) = match self.pricing {
+            Pricing::FirstPrice => {
+                let (w, highest) = auction_circuit(
+                    self.server_key,
+                    &bids,
+                    self.bid_bits,
+                    bids.len(),
+                    self.options,
+                )?;
+                (w, highest.clone(), highest)
+            }
+            Pricing::SecondPrice => {
+                let SecondPriceOutput {
+                    winners,
+                    highest,
+                    price,
+                } = second_price_circuit(
+                    self.server_key,
+                    &bids,
+                    self.bid_bits,
+                    bids.len(),
+                    self.options,
+                )?;
+                (winners, highest, price)
+            }
+        };
+
+        if let Some(policy) = &self.tie_break {
+            winners = tie_break(self.server_key, &winners, policy)?;
+        }
+
+        let reserve_met = match self.reserve {
+            Some(reserve) => {
+                let reserve = self.direction.orient(self.server_key, reserve);
+                let met = greater_or_equal(self.server_key, &highest, &reserve)?;
+                if self.pricing == Pricing::SecondPrice {
+                    amount = max(self.server_key, &amount, &reserve, self.options.multiplexer)?;
+                }
+                winners = mask(self.server_key, &met, &winners)?;
+                Some(met)
+            }
+            None => None,
+        };
+
+        amount = self.direction.orient(self.server_key, &amount);
+        if let Some(met) = &reserve_met {
+            amount = mask(self.server_key, met, &amount)?;
+        }
+
+        Ok(AuctionResult {
+            winners,
+            amount,
+            reserve_met,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+    use crate::test_utils::{decrypt_amount, decrypt_indices, encrypt_bid};
+
+    #[test]
+    fn reverse_auction_works() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+
+        // (bids, pricing, expected winners, expected amount)
+        let cases = vec![
+            (vec![90, 12, 255, 40], Pricing::FirstPrice, vec![1], 12),
+            (vec![90, 12, 255, 12], Pricing::FirstPrice, vec![1, 3], 12),
+            (vec![90, 12, 255, 40], Pricing::SecondPrice, vec![1], 40),
+            (vec![90, 12, 255, 12], Pricing::SecondPrice, vec![1, 3], 12),
+        ];
+
+        for (bids, pricing, expected_winners, expected_amount) in cases {
+            let encrypted_bids = bids
+                .iter()
+                .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+                .collect::<Vec<_>>();
+            let res = Auction::new(&server_key, bid_bits)
+                .with_pricing(pricing)
+                .with_direction(Direction::Reverse)
+                .run(&encrypted_bids)?;
+
+            assert_eq!(decrypt_indices(&client_key, &res.winners), expected_winners);
+            assert_eq!(decrypt_amount(&client_key, &res.amount), expected_amount);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn reserve_price_works() -> Result<(), Box<dyn std::error::Error>> {
+        use Direction::*;
+        use Pricing::*;
+
+        let bid_bits = 8;
+        let bids = [90, 12, 150];
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let encrypted_bids = bids
+            .iter()
+            .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        // (reserve, pricing, direction, expected met, expected winners, expected amount)
+        let cases = vec![
+            (100, FirstPrice, Forward, true, vec![2], 150),
+            (150, FirstPrice, Forward, true, vec![2], 150),
+            (151, FirstPrice, Forward, false, vec![], 0),
+            (100, SecondPrice, Forward, true, vec![2], 100),
+            (50, SecondPrice, Forward, true, vec![2], 90),
+            (50, FirstPrice, Reverse, true, vec![1], 12),
+            (10, FirstPrice, Reverse, false, vec![], 0),
+            (50, SecondPrice, Reverse, true, vec![1], 50),
+            (100, SecondPrice, Reverse, true, vec![1], 90),
+        ];
+
+        for (reserve, pricing, direction, met, expected_winners, expected_amount) in cases {
+            let encrypted_reserve = encrypt_bid(&client_key, reserve, bid_bits);
+            let res = Auction::new(&server_key, bid_bits)
+                .with_pricing(pricing)
+                .with_direction(direction)
+                .with_reserve(&encrypted_reserve)
+                .run(&encrypted_bids)?;
+
+            assert_eq!(client_key.decrypt(res.reserve_met.as_ref().unwrap()), met);
+            assert_eq!(decrypt_indices(&client_key, &res.winners), expected_winners);
+            assert_eq!(decrypt_amount(&client_key, &res.amount), expected_amount);
+        }
+
+        let mut trivial_reserve = encrypt_bid(&client_key, 100, bid_bits);
+        trivial_reserve[3] = server_key.trivial(true);
+        assert_eq!(
+            Auction::new(&server_key, bid_bits)
+                .with_reserve(&trivial_reserve)
+                .run(&encrypted_bids)
+                .err(),
+            Some(AuctionError::ReserveBit { bit: 3 })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tie_break_selects_single_winner() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let bids = [40, 200, 7, 200, 200];
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let encrypted_bids = bids
+            .iter()
+            .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        let res = Auction::new(&server_key, bid_bits)
+            .with_pricing(Pricing::SecondPrice)
+            .with_tie_break(TieBreak::Priority(vec![4, 3, 2, 1, 0]))
+            .run(&encrypted_bids)?;
+        assert_eq!(decrypt_indices(&client_key, &res.winners), vec![4]);
+        assert_eq!(decrypt_amount(&client_key, &res.amount), 200);
+
+        Ok(())
+    }
+
+    #[test]
+    fn early_decrypt_matches_circuit() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let bids = [40, 200, 7, 200, 13];
+        let (mut client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let encrypted_bids = bids
+            .iter()
+            .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        let auction = Auction::new(&server_key, bid_bits);
+        let res = auction.run_early_decrypt(&encrypted_bids, &mut client_key)?;
+        assert_eq!(decrypt_indices(&client_key, &res.winners), vec![1, 3]);
+        assert_eq!(res.amount_u64(), 200);
+
+        // oracle sees exactly one decryption per bit
+        let mut rounds = vec![];
+        let oracle_key = client_key.clone();
+        let mut oracle = |round: usize, ct: &Ciphertext| {
+            rounds.push(round);
+            Ok(oracle_key.decrypt(ct))
+        };
+        let res = Auction::new(&server_key, bid_bits)
+            .with_direction(Direction::Reverse)
+            .with_tie_break(TieBreak::LowestIndex)
+            .run_early_decrypt(&encrypted_bids, &mut oracle)?;
+        assert_eq!(decrypt_indices(&client_key, &res.winners), vec![2]);
+        assert_eq!(res.amount_u64(), 7);
+        assert_eq!(rounds, (0..bid_bits).collect::<Vec<_>>());
+
+        assert_eq!(
+            auction
+                .with_pricing(Pricing::SecondPrice)
+                .run_early_decrypt(&encrypted_bids, &mut client_key)
+                .err(),
+            Some(AuctionError::Unsupported(
+                "early decryption requires first price"
+            ))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{
+    cost::{CostReport, CountingEngine},
+    AuctionError, Multiplexer, OrReduction, PlaintextEngine,
+};
+
+/// Gate level choices shared by all auction circuits
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitOptions {
+    pub or_reduction: OrReduction,
+    pub multiplexer: Multiplexer,
+    /// No. of threads used to evaluate per bidder gates. If not set gates run on
+    /// rayon's global thread pool. Only has an effect with `parallel` feature.
+    pub threads: Option<usize>,
+}
+
+/// Builder methods shared by [Auction](crate::Auction), [TopK](crate::TopK) and
+/// [MultiUnit](crate::MultiUnit)
+pub trait CircuitBuilder: Sized {
+    fn options(&self) -> &CircuitOptions;
+
+    fn options_mut(&mut self) -> &mut CircuitOptions;
+
+    /// Runs the circuit with `options` on `engine` over `bidder_count` bids of
+    /// zeros
+    fn count_gates(
+        &self,
+        engine: &CountingEngine<PlaintextEngine>,
+        bidder_count: usize,
+        options: CircuitOptions,
+    ) -> Result<(), AuctionError>;
+
+    fn with_or_reduction(mut self, or_reduction: OrReduction) -> Self {
+        self.options_mut().or_reduction = or_reduction;
+        self
+    }
+
+    fn with_multiplexer(mut self, multiplexer: Multiplexer) -> Self {
+        self.options_mut().multiplexer = multiplexer;
+        self
+    }
+
+    /// Sets no. of threads used to evaluate per bidder gates. Only has an effect
+    /// with `parallel` feature.
+    fn with_threads(mut self, threads: usize) -> Self {
+        self.options_mut().threads = Some(threads);
+        self
+    }
+
+    /// Counts gates the circuit evaluates over `bidder_count` bidders.
+    ///
+    /// Auction circuits are data oblivious, thus gates are counted by running the
+    /// circuit with the same options on [PlaintextEngine]. The circuit runs on a
+    /// single thread, so that peak ciphertexts do not depend on scheduling.
+    fn cost_report(&self, bidder_count: usize) -> Result<CostReport, AuctionError> {
+        let engine = CountingEngine::new(&PlaintextEngine);
+        let options = CircuitOptions {
+            threads: Some(1),
+            ..*self.options()
+        };
+        self.count_gates(&engine, bidder_count, options)?;
+        Ok(engine.report())
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::{AuctionError, BidBit, BooleanEngine};
+
+/// No. of gates evaluated by an auction circuit
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CostReport {
+    pub and: usize,
+    pub or: usize,
+    /// NOT gates only negate the ciphertext and do not bootstrap
+    pub not: usize,
+    /// Single bootstrap multiplexers in 7-encoding
+    pub mux: usize,
+    /// Bootstraps on the longest sequential chain, counted as in
+    /// [CostReport::bootstraps]
+    pub depth: usize,
+    /// Most ciphertexts alive at once, including bids
+    pub peak_ciphertexts: usize,
+}
+
+impl CostReport {
+    /// Total no. of gates including NOT
+    pub fn gates(&self) -> usize {
+        self.and + self.or + self.not + self.mux
+    }
+
+    /// Total no. of bootstraps in 2 bit plaintext space. A 7-encoding
+    /// multiplexer bootstraps with 3 bit plaintext space, thus costs roughly
+    /// twice an AND or OR and is counted as two bootstraps.
+    ///
+    /// Gates with a trivial input are counted as well, thus this is an upper
+    /// bound for engines that evaluate such gates without bootstrapping.
+    pub fn bootstraps(&self) -> usize {
+        self.and + self.or + 2 * self.mux
+    }
+}
+
+/// No. of ciphertexts alive and the most alive at once
+#[derive(Default)]
+struct Live {
+    live: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Live {
+    fn acquire(live: &Arc<Live>) -> Arc<Live> {
+        let now = live.live.fetch_add(1, Ordering::Relaxed) + 1;
+        live.peak.fetch_max(now, Ordering::Relaxed);
+        live.clone()
+    }
+}
+
+/// Ciphertext evaluated through [CountingEngine].
+///
+/// Carries no. of bootstraps on its longest path from the inputs and is counted
+/// as alive until dropped.
+pub struct Counted<C> {
+    ct: C,
+    depth: usize,
+    live: Arc<Live>,
+}
+
+impl<C> Counted<C> {
+    pub fn inner(&self) -> &C {
+        &self.ct
+    }
+}
+
+impl<C: Clone> Clone for Counted<C> {
+    fn clone(&self) -> Self {
+        Counted {
+            ct: self.ct.clone(),
+            depth: self.depth,
+            live: Live::acquire(&self.live),
+        }
+    }
+}
+
+impl<C> Drop for Counted<C> {
+    fn drop(&mut self) {
+        self.live.live.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl<C: BidBit> BidBit for Counted<C> {
+    fn validate(&self, bidder: usize, bit: usize) -> Result<(), AuctionError> {
+        self.ct.validate(bidder, bit)
+    }
+}
+
+/// Wraps an engine and counts the gates evaluated through it.
+///
+/// Counters are atomic, thus gates evaluated in parallel are counted as well.
+/// Trivial constants are not gates and are not counted. Ciphertexts are wrapped
+/// in [Counted] to track the longest chain of bootstraps and the most
+/// ciphertexts alive at once, thus inputs must be wrapped with
+/// [CountingEngine::wrap].
+pub struct CountingEngine<'a, E> {
+    engine: &'a E,
+    and: AtomicUsize,
+    or: AtomicUsize,
+    not: AtomicUsize,
+    mux: AtomicUsize,
+    depth: AtomicUsize,
+    live: Arc<Live>,
+}
+
+impl<'a, E: BooleanEngine> CountingEngine<'a, E> {
+    pub fn new(engine: &'a E) -> Self {
+        CountingEngine {
+            engine,
+            and: AtomicUsize::new(0),
+            or: AtomicUsize::new(0),
+            not: AtomicUsize::new(0),
+            mux: AtomicUsize::new(0),
+            depth: AtomicUsize::new(0),
+            live: Arc::default(),
+        }
+    }
+
+    /// Wraps an input ciphertext, for ex. a bid bit
+    pub fn wrap(&self, ct: E::Ciphertext) -> Counted<E::Ciphertext> {
+        self.counted(ct, 0)
+    }
+
+    fn counted(&self, ct: E::Ciphertext, depth: usize) -> Counted<E::Ciphertext> {
+        self.depth.fetch_max(depth, Ordering::Relaxed);
+        Counted {
+            ct,
+            depth,
+            live: Live::acquire(&self.live),
+        }
+    }
+
+    /// Returns gates counted since creation or last [CountingEngine::reset]
+    pub fn report(&self) -> CostReport {
+        CostReport {
+            and: self.and.load(Ordering::Relaxed),
+            or: self.or.load(Ordering::Relaxed),
+            not: self.not.load(Ordering::Relaxed),
+            mux: self.mux.load(Ordering::Relaxed),
+            depth: self.depth.load(Ordering::Relaxed),
+            peak_ciphertexts: self.live.peak.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets counters. Peak restarts from the ciphertexts still alive.
+    pub fn reset(&self) {
+        self.and.store(0, Ordering::Relaxed);
+        self.or.store(0, Ordering::Relaxed);
+        self.not.store(0, Ordering::Relaxed);
+        self.mux.store(0, Ordering::Relaxed);
+        self.depth.store(0, Ordering::Relaxed);
+        self.live
+            .peak
+            .store(self.live.live.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
+impl<'a, E: BooleanEngine> BooleanEngine for CountingEngine<'a, E> {
+    type Ciphertext = Counted<E::Ciphertext>;
+
+    fn and(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        self.and.fetch_add(1, Ordering::Relaxed);
+        let ct = self.engine.and(&a.ct, &b.ct)?;
+        Ok(self.counted(ct, a.depth.max(b.depth) + 1))
+    }
+
+    fn or(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        self.or.fetch_add(1, Ordering::Relaxed);
+        let ct = self.engine.or(&a.ct, &b.ct)?;
+        Ok(self.counted(ct, a.depth.max(b.depth) + 1))
+    }
+
+    fn not(&self, a: &Self::Ciphertext) -> Self::Ciphertext {
+        self.not.fetch_add(1, Ordering::Relaxed);
+        self.counted(self.engine.not(&a.ct), a.depth)
+    }
+
+    fn trivial(&self, value: bool) -> Self::Ciphertext {
+        self.counted(self.engine.trivial(value), 0)
+    }
+
+    fn seven_encoding_mux(
+        &self,
+        b: &Self::Ciphertext,
+        x: &Self::Ciphertext,
+        y: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        self.mux.fetch_add(1, Ordering::Relaxed);
+        let ct = self.engine.seven_encoding_mux(&b.ct, &x.ct, &y.ct)?;
+        Ok(self.counted(ct, b.depth.max(x.depth).max(y.depth) + 2))
+    }
+}
+
+/// Predicts resources of an auction from its [CostReport].
+///
+/// Bootstrap time is calibrated once on the current machine, since it dominates
+/// the runtime and varies widely across machines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostModel {
+    bootstrap: Duration,
+    ciphertext_bytes: usize,
+    server_key_bytes: usize,
+}
+
+/// Predicted resources of an auction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Estimate {
+    /// Wall time if threads are perfectly utilized, i.e. the larger of the work
+    /// split over all threads and the longest sequential chain
+    pub min_wall_time: Duration,
+    /// Wall time if only the longest sequential chain runs on a single thread
+    /// while the remaining work is split over all threads
+    pub max_wall_time: Duration,
+    /// Peak memory held by ciphertexts and the server key during the auction
+    pub memory_bytes: usize,
+    /// Size of ciphertexts uploaded by all bidders
+    pub upload_bytes: usize,
+}
+
+impl CostModel {
+    /// `bootstrap` is the time of a single AND/OR, `ciphertext_bytes` the size
+    /// of a single encrypted bit and `server_key_bytes` the size of the server
+    /// key
+    pub fn new(bootstrap: Duration, ciphertext_bytes: usize, server_key_bytes: usize) -> Self {
+        CostModel {
+            bootstrap,
+            ciphertext_bytes,
+            server_key_bytes,
+        }
+    }
+
+    /// Calibrates bootstrap time as the mean of `samples` ANDs of `ct` with
+    /// itself. `ct` must be encrypted, since gates over trivial ciphertexts may
+    /// not bootstrap.
+    ///
+    /// Ciphertext size is the serialized size of `ct`, which is what bidders
+    /// upload. Server key size is its serialized size as well.
+    pub fn calibrate<E>(
+        server_key: &E,
+        ct: &E::Ciphertext,
+        samples: usize,
+    ) -> Result<Self, AuctionError>
+    where
+        E: BooleanEngine + Serialize,
+        E::Ciphertext: Serialize,
+    {
+        let ciphertext_bytes = bincode::serialized_size(ct)
+            .map_err(|e| AuctionError::Serialize(e.to_string()))?
+            as usize;
+        let server_key_bytes = bincode::serialized_size(server_key)
+            .map_err(|e| AuctionError::Serialize(e.to_string()))?
+            as usize;
+
+        let samples = samples.max(1);
+        // warm up caches before timing
+        server_key.and(ct, ct)?;
+
+        let now = Instant::now();
+        for _ in 0..samples {
+            server_key.and(ct, ct)?;
+        }
+        let bootstrap = now.elapsed() / samples as u32;
+
+        Ok(CostModel::new(
+            bootstrap,
+            ciphertext_bytes,
+            server_key_bytes,
+        ))
+    }
+
+    pub fn bootstrap(&self) -> Duration {
+        self.bootstrap
+    }
+
+    pub fn ciphertext_bytes(&self) -> usize {
+        self.ciphertext_bytes
+    }
+
+    pub fn server_key_bytes(&self) -> usize {
+        self.server_key_bytes
+    }
+
+    /// Predicts resources of an auction with `report` gates over `bidder_count`
+    /// bids of `bid_bits` bits evaluated on `threads` threads.
+    ///
+    /// Wall time is bounded by `max(W / p, D)` and `(W - D) / p + D` bootstraps,
+    /// where `W` is the total no. of bootstraps, `D` the depth and `p` no. of
+    /// threads. Threads are ignored without `parallel` feature.
+    ///
+    /// Memory is taken from the peak ciphertexts of `report`, which
+    /// [cost_report](crate::CircuitBuilder::cost_report) counts on a single
+    /// thread. Each additional thread holds a few more temporary ciphertexts.
+    pub fn estimate(
+        &self,
+        report: &CostReport,
+        bidder_count: usize,
+        bid_bits: usize,
+        threads: usize,
+    ) -> Estimate {
+        let threads = if cfg!(feature = "parallel") {
+            threads.max(1)
+        } else {
+            1
+        };
+        let work = report.bootstraps();
+        let depth = report.depth.min(work);
+
+        let min_wall_time =
+            (self.bootstrap * work as u32 / threads as u32).max(self.bootstrap * depth as u32);
+        let max_wall_time =
+            self.bootstrap * (work - depth) as u32 / threads as u32 + self.bootstrap * depth as u32;
+
+        Estimate {
+            min_wall_time,
+            max_wall_time,
+            memory_bytes: report.peak_ciphertexts * self.ciphertext_bytes + self.server_key_bytes,
+            upload_bytes: bidder_count * bid_bits * self.ciphertext_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+    use crate::{
+        test_utils::to_bits, Auction, CircuitBuilder, OrReduction, PlaintextEngine, Pricing, TopK,
+    };
+
+    #[test]
+    fn counts_first_price_gates() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 4;
+        let bids = [5, 11, 3];
+        let n = bids.len();
+        let plain_bids = bids
+            .iter()
+            .map(|bid| to_bits(*bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        let engine = CountingEngine::new(&PlaintextEngine);
+        let counted_bids = plain_bids
+            .iter()
+            .map(|bid| bid.iter().map(|bit| engine.wrap(*bit)).collect())
+            .collect::<Vec<_>>();
+        Auction::new(&engine, bid_bits)
+            .with_threads(1)
+            .run(&counted_bids)?;
+
+        // per bit n ANDs, n - 1 ORs and a 3 gate multiplexer per bidder
+        let report = engine.report();
+        assert_eq!(report.and, bid_bits * 3 * n);
+        assert_eq!(report.or, bid_bits * (2 * n - 1));
+        assert_eq!(report.not, bid_bits * n);
+        assert_eq!(report.bootstraps(), bid_bits * (5 * n - 1));
+        assert!(report.depth < report.bootstraps());
+        assert!(report.peak_ciphertexts > n * bid_bits);
+
+        // counts do not depend on the bids
+        assert_eq!(
+            Auction::new(&PlaintextEngine, bid_bits).cost_report(n)?,
+            report
+        );
+
+        // bids are still alive
+        engine.reset();
+        assert_eq!(
+            engine.report(),
+            CostReport {
+                peak_ciphertexts: n * bid_bits,
+                ..CostReport::default()
+            }
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cost_report_matches_estimates() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let n = 10;
+
+        let first = Auction::new(&PlaintextEngine, bid_bits).cost_report(n)?;
+        let second = Auction::new(&PlaintextEngine, bid_bits)
+            .with_pricing(Pricing::SecondPrice)
+            .cost_report(n)?;
+        // second price runs bit-slice max twice, detects ties and muxes the price
+        assert_eq!(
+            second.bootstraps(),
+            2 * first.bootstraps() + 3 * n + 3 * bid_bits
+        );
+
+        // each slot runs bit-slice max and tie break, all but the last mask
+        for slots in 1..=3 {
+            let top_k = TopK::new(&PlaintextEngine, bid_bits, slots);
+            assert_eq!(
+                top_k.cost_report(n)?.bootstraps(),
+                slots * (bid_bits * (5 * n - 1) + 2 * n) + (slots - 1) * n
+            );
+        }
+
+        // tree reduction shortens the chain but evaluates the same gates
+        let chain = Auction::new(&PlaintextEngine, bid_bits)
+            .with_or_reduction(OrReduction::Chain)
+            .cost_report(n)?;
+        assert_eq!(chain.bootstraps(), first.bootstraps());
+        assert!(chain.depth > first.depth);
+
+        Ok(())
+    }
+
+    #[test]
+    fn cost_model_estimates() -> Result<(), Box<dyn std::error::Error>> {
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let ct = client_key.encrypt(true);
+        let model = CostModel::calibrate(&server_key, &ct, 4)?;
+        assert_eq!(
+            model.ciphertext_bytes() as u64,
+            bincode::serialized_size(&ct)?
+        );
+        assert_eq!(
+            model.server_key_bytes() as u64,
+            bincode::serialized_size(&server_key)?
+        );
+
+        let model = CostModel::new(Duration::from_millis(10), 1000, 50_000);
+        let report = CostReport {
+            and: 60,
+            or: 40,
+            not: 20,
+            mux: 0,
+            depth: 20,
+            peak_ciphertexts: 70,
+        };
+        assert_eq!(report.bootstraps(), 100);
+        let estimate = model.estimate(&report, 4, 8, 1);
+        assert_eq!(estimate.min_wall_time, Duration::from_secs(1));
+        assert_eq!(estimate.max_wall_time, Duration::from_secs(1));
+        assert_eq!(estimate.memory_bytes, 70 * 1000 + 50_000);
+        assert_eq!(estimate.upload_bytes, 4 * 8 * 1000);
+
+        // work is split over threads, the chain is not
+        let estimate = model.estimate(&report, 4, 8, 4);
+        if cfg!(feature = "parallel") {
+            assert_eq!(estimate.min_wall_time, Duration::from_millis(250));
+            assert_eq!(estimate.max_wall_time, Duration::from_millis(400));
+            assert_eq!(
+                model.estimate(&report, 4, 8, 16).min_wall_time,
+                Duration::from_millis(200)
+            );
+        } else {
+            assert_eq!(estimate.min_wall_time, Duration::from_secs(1));
+            assert_eq!(estimate.max_wall_time, Duration::from_secs(1));
+        }
+
+        // a 7-encoding multiplexer counts as two bootstraps
+        let report = CostReport {
+            mux: 50,
+            ..CostReport::default()
+        };
+        assert_eq!(report.bootstraps(), 100);
+        assert_eq!(
+            model.estimate(&report, 4, 8, 1).max_wall_time,
+            Duration::from_secs(1)
+        );
+
+        Ok(())
+    }
+}
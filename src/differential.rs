@@ -0,0 +1,279 @@
+//! Differential tests of auction circuits.
+//!
+//! Thousands of random auctions are run on [PlaintextEngine] and checked
+//! against a straightforward reference on `u64` bids. A smaller sample is run
+//! under FHE and checked against [PlaintextEngine], which evaluates the exact
+//! same gates.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+use crate::{
+    test_utils::{decrypt_amount, decrypt_indices, encrypt_bid, from_bits, to_bits},
+    Auction, BooleanEngine, Direction, PlaintextEngine, Pricing, TieBreak, TopK,
+};
+
+/// Random auction configuration
+#[derive(Clone, Debug)]
+struct Case {
+    bids: Vec<u64>,
+    bid_bits: usize,
+    pricing: Pricing,
+    direction: Direction,
+    reserve: Option<u64>,
+    tie_break: bool,
+}
+
+/// Expected output of an auction
+#[derive(Debug, PartialEq, Eq)]
+struct Outcome {
+    winners: Vec<usize>,
+    amount: u64,
+    reserve_met: Option<bool>,
+}
+
+impl Case {
+    /// Samples a case with up to `max_bidders` bidders and `max_bits` bits.
+    /// Bids are biased towards ties, all-zero bids and a single bidder.
+    fn sample(rng: &mut StdRng, max_bidders: usize, max_bits: usize) -> Self {
+        let bid_bits = rng.gen_range(1..=max_bits);
+        let bidders = if rng.gen_bool(0.1) {
+            1
+        } else {
+            rng.gen_range(1..=max_bidders)
+        };
+        let top = u64::MAX >> (64 - bid_bits);
+
+        let mut bids = (0..bidders)
+            .map(|_| rng.gen_range(0..=top))
+            .collect::<Vec<_>>();
+        match rng.gen_range(0..4) {
+            0 => bids.iter_mut().for_each(|bid| *bid = 0),
+            1 => {
+                // copy a bid over a few others
+                let bid = bids[rng.gen_range(0..bidders)];
+                for _ in 0..rng.gen_range(1..=bidders) {
+                    bids[rng.gen_range(0..bidders)] = bid;
+                }
+            }
+            _ => {}
+        }
+
+        Case {
+            bids,
+            bid_bits,
+            pricing: if rng.gen_bool(0.5) {
+                Pricing::FirstPrice
+            } else {
+                Pricing::SecondPrice
+            },
+            direction: if rng.gen_bool(0.5) {
+                Direction::Forward
+            } else {
+                Direction::Reverse
+            },
+            reserve: rng.gen_bool(0.3).then(|| rng.gen_range(0..=top)),
+            tie_break: rng.gen_bool(0.3),
+        }
+    }
+
+    fn top(&self) -> u64 {
+        u64::MAX >> (64 - self.bid_bits)
+    }
+
+    /// Maps amounts such that the winning bid is the highest
+    fn orient(&self, amount: u64) -> u64 {
+        match self.direction {
+            Direction::Forward => amount,
+            Direction::Reverse => self.top() - amount,
+        }
+    }
+
+    /// Computes the outcome directly on plaintext bids
+    fn reference(&self) -> Outcome {
+        let oriented = self
+            .bids
+            .iter()
+            .map(|bid| self.orient(*bid))
+            .collect::<Vec<_>>();
+        let highest = *oriented.iter().max().unwrap();
+        let mut winners = (0..oriented.len())
+            .filter(|j| oriented[*j] == highest)
+            .collect::<Vec<_>>();
+
+        let mut price = match self.pricing {
+            Pricing::FirstPrice => highest,
+            Pricing::SecondPrice if winners.len() > 1 => highest,
+            Pricing::SecondPrice => oriented
+                .iter()
+                .filter(|o| **o != highest)
+                .max()
+                .copied()
+                .unwrap_or(0),
+        };
+        if self.tie_break {
+            winners.truncate(1);
+        }
+
+        let reserve_met = self.reserve.map(|reserve| {
+            let reserve = self.orient(reserve);
+            if self.pricing == Pricing::SecondPrice {
+                price = price.max(reserve);
+            }
+            highest >= reserve
+        });
+
+        let mut amount = self.orient(price);
+        if reserve_met == Some(false) {
+            winners.clear();
+            amount = 0;
+        }
+
+        Outcome {
+            winners,
+            amount,
+            reserve_met,
+        }
+    }
+
+    fn auction<'a, E: BooleanEngine>(
+        &self,
+        server_key: &'a E,
+        reserve: Option<&'a [E::Ciphertext]>,
+    ) -> Auction<'a, E> {
+        let mut auction = Auction::new(server_key, self.bid_bits)
+            .with_pricing(self.pricing)
+            .with_direction(self.direction);
+        if let Some(reserve) = reserve {
+            auction = auction.with_reserve(reserve);
+        }
+        if self.tie_break {
+            auction = auction.with_tie_break(TieBreak::LowestIndex);
+        }
+        auction
+    }
+
+    /// Runs the auction circuit on [PlaintextEngine]
+    fn plaintext(&self) -> Outcome {
+        let bids = self
+            .bids
+            .iter()
+            .map(|bid| to_bits(*bid, self.bid_bits))
+            .collect::<Vec<_>>();
+        let reserve = self.reserve.map(|reserve| to_bits(reserve, self.bid_bits));
+        let res = self
+            .auction(&PlaintextEngine, reserve.as_deref())
+            .run(&bids)
+            .unwrap();
+
+        Outcome {
+            winners: (0..res.winners.len()).filter(|j| res.winners[*j]).collect(),
+            amount: from_bits(&res.amount),
+            reserve_met: res.reserve_met,
+        }
+    }
+}
+
+#[test]
+fn plaintext_auction_matches_reference() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..5000 {
+        let case = Case::sample(&mut rng, 16, 16);
+        assert_eq!(case.plaintext(), case.reference(), "{case:?}");
+    }
+}
+
+#[test]
+fn plaintext_early_decrypt_matches_reference() {
+    let mut rng = StdRng::seed_from_u64(1);
+    for _ in 0..2000 {
+        let mut case = Case::sample(&mut rng, 16, 16);
+        case.pricing = Pricing::FirstPrice;
+        case.reserve = None;
+
+        let bids = case
+            .bids
+            .iter()
+            .map(|bid| to_bits(*bid, case.bid_bits))
+            .collect::<Vec<_>>();
+        let mut oracle = |_, b: &bool| Ok(*b);
+        let res = case
+            .auction(&PlaintextEngine, None)
+            .run_early_decrypt(&bids, &mut oracle)
+            .unwrap();
+
+        let outcome = Outcome {
+            winners: (0..res.winners.len()).filter(|j| res.winners[*j]).collect(),
+            amount: res.amount_u64(),
+            reserve_met: None,
+        };
+        assert_eq!(outcome, case.reference(), "{case:?}");
+    }
+}
+
+#[test]
+fn plaintext_top_k_matches_reference() {
+    let mut rng = StdRng::seed_from_u64(2);
+    for _ in 0..2000 {
+        let case = Case::sample(&mut rng, 12, 12);
+        let slots = rng.gen_range(1..=case.bids.len());
+
+        // best bid first, ties towards the lowest index
+        let mut expected = (0..case.bids.len()).collect::<Vec<_>>();
+        expected.sort_by_key(|j| std::cmp::Reverse(case.orient(case.bids[*j])));
+        let expected = expected
+            .into_iter()
+            .take(slots)
+            .map(|j| (j, case.bids[j]))
+            .collect::<Vec<_>>();
+
+        let bids = case
+            .bids
+            .iter()
+            .map(|bid| to_bits(*bid, case.bid_bits))
+            .collect::<Vec<_>>();
+        let res = TopK::new(&PlaintextEngine, case.bid_bits, slots)
+            .with_direction(case.direction)
+            .run(&bids)
+            .unwrap();
+        let ranking = res
+            .winners
+            .iter()
+            .zip(res.amounts.iter())
+            .map(|(w, amount)| {
+                let w = (0..w.len()).filter(|j| w[*j]).collect::<Vec<_>>();
+                assert_eq!(w.len(), 1, "{case:?}");
+                (w[0], from_bits(amount))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(ranking, expected, "{case:?}");
+    }
+}
+
+#[test]
+fn encrypted_auction_matches_plaintext() -> Result<(), Box<dyn std::error::Error>> {
+    let mut rng = StdRng::seed_from_u64(3);
+    let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+    for _ in 0..8 {
+        let case = Case::sample(&mut rng, 4, 4);
+
+        let bids = case
+            .bids
+            .iter()
+            .map(|bid| encrypt_bid(&client_key, *bid, case.bid_bits))
+            .collect::<Vec<_>>();
+        let reserve = case
+            .reserve
+            .map(|reserve| encrypt_bid(&client_key, reserve, case.bid_bits));
+        let res = case.auction(&server_key, reserve.as_deref()).run(&bids)?;
+
+        let outcome = Outcome {
+            winners: decrypt_indices(&client_key, &res.winners),
+            amount: decrypt_amount(&client_key, &res.amount),
+            reserve_met: res.reserve_met.map(|met| client_key.decrypt(&met)),
+        };
+        assert_eq!(outcome, case.plaintext(), "{case:?}");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use tfhe::gadget::{ciphertext::Ciphertext, client_key::ClientKey};
+
+use crate::{parallel::try_map, reduce::or_reduce, AuctionError, BooleanEngine, CircuitOptions};
+
+/// Decrypts the i^th bit of the winning amount during the auction.
+///
+/// Implemented by whoever can decrypt, for ex. the client key holder or a
+/// threshold committee. `round` is the index of the bit from MSB, which is
+/// useful for interactive protocols to label messages.
+pub trait DecryptionOracle<C> {
+    fn decrypt_bit(&mut self, round: usize, ct: &C) -> Result<bool, AuctionError>;
+}
+
+impl DecryptionOracle<Ciphertext> for ClientKey {
+    fn decrypt_bit(&mut self, _round: usize, ct: &Ciphertext) -> Result<bool, AuctionError> {
+        Ok(self.decrypt(ct))
+    }
+}
+
+impl<C, F> DecryptionOracle<C> for F
+where
+    F: FnMut(usize, &C) -> Result<bool, AuctionError>,
+{
+    fn decrypt_bit(&mut self, round: usize, ct: &C) -> Result<bool, AuctionError> {
+        self(round, ct)
+    }
+}
+
+/// Output of auction with early decryption
+#[derive(Clone)]
+pub struct EarlyDecryptResult<C = Ciphertext> {
+    /// `winners[j]` encrypts 1 iff j^th bidder placed the winning bid
+    pub winners: Vec<C>,
+    /// Winning amount bits from MSB to LSB, in plaintext
+    pub amount: Vec<bool>,
+}
+
+impl<C> EarlyDecryptResult<C> {
+    /// Returns winning amount. Only valid for bids of at most 64 bits.
+    pub fn amount_u64(&self) -> u64 {
+        self.amount
+            .iter()
+            .fold(0u64, |acc, bit| (acc << 1) | *bit as u64)
+    }
+}
+
+/// Bit-slice max that decrypts `b` of each bit iteration via `oracle`.
+///
+/// Since `b` is the i^th bit of the winning amount, which is revealed anyways,
+/// decrypting it before the multiplexer turns the multiplexer into a plaintext
+/// selection: if `b = 1` bidders with 0 at i^th bit drop out, i.e. `w = s`,
+/// otherwise `w` stays the same. This saves 3 gates per bidder per bit at the
+/// cost of one decryption per bit. Bids must be validated by the caller.
+pub(crate) fn early_decrypt_circuit<
+    E: BooleanEngine,
+    O: DecryptionOracle<E::Ciphertext> + ?Sized,
+>(
+    server_key: &E,
+    bids: &[Vec<E::Ciphertext>],
+    bid_bits: usize,
+    oracle: &mut O,
+    options: CircuitOptions,
+) -> Result<(Vec<E::Ciphertext>, Vec<bool>), AuctionError> {
+    let mut w = vec![server_key.trivial(true); bids.len()];
+    let mut amount = vec![false; bid_bits];
+    for i in 0..bid_bits {
+        // AND at i^th MSB of j^th bidder
+        let s = try_map(&w, |j, w_j| server_key.and(w_j, &bids[j][i]))?;
+
+        let b = or_reduce(server_key, s.clone(), options.or_reduction)?;
+        let b = oracle.decrypt_bit(i, &b)?;
+        if b {
+            w = s;
+        }
+        // set i^th MSB of amount
+        amount[i] = b;
+    }
+
+    Ok((w, amount))
+}
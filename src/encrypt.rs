@@ -0,0 +1,108 @@
+use tfhe::{boolean, gadget};
+
+use crate::AuctionError;
+
+/// Key a bidder encrypts their bid with.
+///
+/// Bidders should only ever receive a public key, since anyone holding the
+/// client key can decrypt every bid. Upstream [tfhe::boolean] supports public
+/// key encryption via [PublicKey](boolean::public_key::PublicKey), thus with
+/// public keys auctions run on the [tfhe::boolean] server key.
+///
+/// The gadget does not expose its LWE secret key nor its p-encoding, thus no
+/// public key can be derived under gadget parameters. Gadget bids still have to
+/// be encrypted with the gadget client key.
+pub trait BidEncryptor {
+    type Ciphertext;
+
+    fn encrypt_bit(&self, bit: bool) -> Self::Ciphertext;
+
+    /// Encrypts `amount` as `bid_bits` bits from MSB to LSB
+    fn encrypt_bid(
+        &self,
+        amount: u64,
+        bid_bits: usize,
+    ) -> Result<Vec<Self::Ciphertext>, AuctionError> {
+        if bid_bits == 0 {
+            return Err(AuctionError::ZeroBidBits);
+        }
+        if bid_bits < 64 && amount >> bid_bits != 0 {
+            return Err(AuctionError::AmountOverflow { amount, bid_bits });
+        }
+        Ok((0..bid_bits)
+            .map(|i| {
+                // bits above the 64th are 0
+                let shift = bid_bits - 1 - i;
+                self.encrypt_bit(shift < 64 && (amount >> shift) & 1 != 0)
+            })
+            .collect())
+    }
+}
+
+impl BidEncryptor for boolean::public_key::PublicKey {
+    type Ciphertext = boolean::ciphertext::Ciphertext;
+
+    fn encrypt_bit(&self, bit: bool) -> Self::Ciphertext {
+        self.encrypt(bit)
+    }
+}
+
+impl BidEncryptor for boolean::client_key::ClientKey {
+    type Ciphertext = boolean::ciphertext::Ciphertext;
+
+    fn encrypt_bit(&self, bit: bool) -> Self::Ciphertext {
+        self.encrypt(bit)
+    }
+}
+
+impl BidEncryptor for gadget::client_key::ClientKey {
+    type Ciphertext = gadget::ciphertext::Ciphertext;
+
+    fn encrypt_bit(&self, bit: bool) -> Self::Ciphertext {
+        self.encrypt(bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::boolean::{gen_keys, public_key::PublicKey};
+
+    use super::*;
+    use crate::Auction;
+
+    #[test]
+    fn public_key_bids_run_auction() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let (client_key, server_key) = gen_keys();
+        // bidders only receive the public key
+        let public_key = PublicKey::new(&client_key);
+
+        let bids = [90, 12, 200, 41]
+            .iter()
+            .map(|amount| public_key.encrypt_bid(*amount, bid_bits))
+            .collect::<Result<Vec<_>, _>>()?;
+        let res = Auction::new(&server_key, bid_bits).run(&bids)?;
+
+        let winners = res
+            .winners
+            .iter()
+            .map(|ct| client_key.decrypt(ct))
+            .collect::<Vec<_>>();
+        let amount = res
+            .amount
+            .iter()
+            .fold(0u64, |acc, ct| (acc << 1) | client_key.decrypt(ct) as u64);
+        assert_eq!(winners, vec![false, false, true, false]);
+        assert_eq!(amount, 200);
+
+        assert_eq!(
+            public_key.encrypt_bid(256, bid_bits).err(),
+            Some(AuctionError::AmountOverflow {
+                amount: 256,
+                bid_bits
+            })
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use tfhe::{boolean, gadget};
+
+use crate::AuctionError;
+
+/// Boolean gates auction circuits are built from.
+///
+/// Implemented for gadget [ServerKey](gadget::server_key::ServerKey), upstream
+/// [tfhe::boolean] server key and [PlaintextEngine], so that the same circuits run
+/// across backends.
+pub trait BooleanEngine: Sync {
+    type Ciphertext: BidBit + Clone + Send + Sync;
+
+    fn and(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError>;
+
+    fn or(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError>;
+
+    fn not(&self, a: &Self::Ciphertext) -> Self::Ciphertext;
+
+    /// Returns unencrypted constant `value`
+    fn trivial(&self, value: bool) -> Self::Ciphertext;
+
+    /// Returns `x` if `b` else `y` as a single bootstrap in 7-encoding
+    fn seven_encoding_mux(
+        &self,
+        _b: &Self::Ciphertext,
+        _x: &Self::Ciphertext,
+        _y: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        Err(AuctionError::Unsupported(
+            "engine does not support 7-encoding multiplexer",
+        ))
+    }
+}
+
+/// Single bit of a bid as submitted by a bidder
+pub trait BidBit {
+    /// Checks the bit is a proper encryption, where bit is the `bit`^th bit of
+    /// bid of `bidder`
+    fn validate(&self, bidder: usize, bit: usize) -> Result<(), AuctionError>;
+}
+
+impl BooleanEngine for gadget::server_key::ServerKey {
+    type Ciphertext = gadget::ciphertext::Ciphertext;
+
+    fn and(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        gadget::server_key::ServerKey::and(self, a, b).map_err(AuctionError::gate)
+    }
+
+    fn or(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        gadget::server_key::ServerKey::or(self, a, b).map_err(AuctionError::gate)
+    }
+
+    fn not(&self, a: &Self::Ciphertext) -> Self::Ciphertext {
+        gadget::server_key::ServerKey::not(self, a)
+    }
+
+    fn trivial(&self, value: bool) -> Self::Ciphertext {
+        gadget::ciphertext::Ciphertext::Trivial(value)
+    }
+
+    fn seven_encoding_mux(
+        &self,
+        b: &Self::Ciphertext,
+        x: &Self::Ciphertext,
+        y: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        self.mux(b, x, y).map_err(AuctionError::gate)
+    }
+}
+
+impl BidBit for gadget::ciphertext::Ciphertext {
+    /// Trivial ciphertexts are not encrypted and leak the bid, thus are never
+    /// accepted from bidders. Neither are placeholders.
+    fn validate(&self, bidder: usize, bit: usize) -> Result<(), AuctionError> {
+        match self {
+            gadget::ciphertext::Ciphertext::Placeholder => {
+                Err(AuctionError::PlaceholderBit { bidder, bit })
+            }
+            gadget::ciphertext::Ciphertext::Trivial(_) => {
+                Err(AuctionError::TrivialBit { bidder, bit })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl BooleanEngine for boolean::server_key::ServerKey {
+    type Ciphertext = boolean::ciphertext::Ciphertext;
+
+    fn and(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        Ok(boolean::server_key::BinaryBooleanGates::and(self, a, b))
+    }
+
+    fn or(
+        &self,
+        a: &Self::Ciphertext,
+        b: &Self::Ciphertext,
+    ) -> Result<Self::Ciphertext, AuctionError> {
+        Ok(boolean::server_key::BinaryBooleanGates::or(self, a, b))
+    }
+
+    fn not(&self, a: &Self::Ciphertext) -> Self::Ciphertext {
+        boolean::server_key::ServerKey::not(self, a)
+    }
+
+    fn trivial(&self, value: bool) -> Self::Ciphertext {
+        self.trivial_encrypt(value)
+    }
+}
+
+impl BidBit for boolean::ciphertext::Ciphertext {
+    fn validate(&self, bidder: usize, bit: usize) -> Result<(), AuctionError> {
+        match self {
+            boolean::ciphertext::Ciphertext::Trivial(_) => {
+                Err(AuctionError::TrivialBit { bidder, bit })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Evaluates gates on plaintext `bool`s.
+///
+/// Executes the exact same gate sequence as encrypted engines, which makes it
+/// useful to test auction circuits quickly.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlaintextEngine;
+
+impl BooleanEngine for PlaintextEngine {
+    type Ciphertext = bool;
+
+    fn and(&self, a: &bool, b: &bool) -> Result<bool, AuctionError> {
+        Ok(*a & *b)
+    }
+
+    fn or(&self, a: &bool, b: &bool) -> Result<bool, AuctionError> {
+        Ok(*a | *b)
+    }
+
+    fn not(&self, a: &bool) -> bool {
+        !*a
+    }
+
+    fn trivial(&self, value: bool) -> bool {
+        value
+    }
+
+    fn seven_encoding_mux(&self, b: &bool, x: &bool, y: &bool) -> Result<bool, AuctionError> {
+        Ok(if *b { *x } else { *y })
+    }
+}
+
+impl BidBit for bool {
+    fn validate(&self, _bidder: usize, _bit: usize) -> Result<(), AuctionError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_utils::{from_bits, to_bits},
+        Auction, Direction, Pricing,
+    };
+
+    #[test]
+    fn plaintext_engine_runs_auction() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let bids = [90u64, 12, 255, 40, 255]
+            .iter()
+            .map(|bid| to_bits(*bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        // (direction, pricing, expected winners, expected amount)
+        let cases = vec![
+            (Direction::Forward, Pricing::FirstPrice, vec![2, 4], 255),
+            (Direction::Forward, Pricing::SecondPrice, vec![2, 4], 255),
+            (Direction::Reverse, Pricing::FirstPrice, vec![1], 12),
+            (Direction::Reverse, Pricing::SecondPrice, vec![1], 40),
+        ];
+
+        for (direction, pricing, expected_winners, expected_amount) in cases {
+            let res = Auction::new(&PlaintextEngine, bid_bits)
+                .with_direction(direction)
+                .with_pricing(pricing)
+                .run(&bids)?;
+
+            let winners = (0..res.winners.len())
+                .filter(|j| res.winners[*j])
+                .collect::<Vec<_>>();
+            assert_eq!(winners, expected_winners);
+            assert_eq!(from_bits(&res.amount), expected_amount);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn boolean_engine_runs_auction() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 4;
+        let (client_key, server_key) = boolean::gen_keys();
+        let bids = [5u64, 11, 3]
+            .iter()
+            .map(|bid| {
+                to_bits(*bid, bid_bits)
+                    .iter()
+                    .map(|bit| client_key.encrypt(*bit))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let res = Auction::new(&server_key, bid_bits).run(&bids)?;
+
+        let winners = res
+            .winners
+            .iter()
+            .map(|ct| client_key.decrypt(ct))
+            .collect::<Vec<_>>();
+        let amount = res
+            .amount
+            .iter()
+            .map(|ct| client_key.decrypt(ct))
+            .collect::<Vec<_>>();
+        assert_eq!(winners, vec![false, true, false]);
+        assert_eq!(from_bits(&amount), 11);
+
+        let trivial = vec![vec![server_key.trivial(true); bid_bits]];
+        assert_eq!(
+            Auction::new(&server_key, bid_bits).run(&trivial).err(),
+            Some(AuctionError::TrivialBit { bidder: 0, bit: 0 })
+        );
+
+        Ok(())
+    }
+}
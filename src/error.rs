@@ -0,0 +1,191 @@
+use std::fmt;
+
+use crate::format::{ParameterSet, PayloadKind};
+
+/// Errors returned by auction circuits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuctionError {
+    /// Auction must have non-zero bid bits
+    ZeroBidBits,
+    /// Auction must have at least one bidder
+    NoBidders,
+    /// No. of bids does not match no. of bidders
+    BidderCount { expected: usize, found: usize },
+    /// Bidder submitted bid with incorrect no. of bits
+    BidLength {
+        bidder: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Bidder submitted a placeholder ciphertext at `bit`
+    PlaceholderBit { bidder: usize, bit: usize },
+    /// Bidder submitted a trivial (unencrypted) ciphertext at `bit`
+    TrivialBit { bidder: usize, bit: usize },
+    /// Amount does not fit in bid bits
+    AmountOverflow { amount: u64, bid_bits: usize },
+    /// Reserve price has incorrect no. of bits
+    ReserveLength { expected: usize, found: usize },
+    /// Reserve price has a placeholder or trivial ciphertext at `bit`
+    ReserveBit { bit: usize },
+    /// Tie break priority is not a permutation of bidder indices
+    InvalidPriority,
+    /// No. of slots must be between 1 and no. of bidders
+    InvalidSlots { slots: usize, bidder_count: usize },
+    /// Multi-unit auction must sell at least one unit
+    ZeroUnits,
+    /// Failed to build thread pool
+    ThreadPool(String),
+    /// Auction configuration is not supported by the chosen circuit
+    Unsupported(&'static str),
+    /// Failed to serialize a ciphertext
+    Serialize(String),
+    /// Decryption oracle failed to decrypt
+    Oracle(String),
+    /// Homomorphic gate evaluation failed
+    Gate(String),
+}
+
+impl AuctionError {
+    pub(crate) fn gate<E: fmt::Display>(e: E) -> Self {
+        AuctionError::Gate(e.to_string())
+    }
+}
+
+impl fmt::Display for AuctionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuctionError::ZeroBidBits => write!(f, "bid bits must be non-zero"),
+            AuctionError::NoBidders => write!(f, "auction has no bidders"),
+            AuctionError::BidderCount { expected, found } => {
+                write!(f, "expected {expected} bids, found {found}")
+            }
+            AuctionError::BidLength {
+                bidder,
+                expected,
+                found,
+            } => write!(
+                f,
+                "bidder {bidder} submitted {found} bits, expected {expected}"
+            ),
+            AuctionError::PlaceholderBit { bidder, bit } => {
+                write!(
+                    f,
+                    "bidder {bidder} submitted placeholder ciphertext at bit {bit}"
+                )
+            }
+            AuctionError::TrivialBit { bidder, bit } => {
+                write!(
+                    f,
+                    "bidder {bidder} submitted trivial ciphertext at bit {bit}"
+                )
+            }
+            AuctionError::AmountOverflow { amount, bid_bits } => {
+                write!(f, "amount {amount} does not fit in {bid_bits} bits")
+            }
+            AuctionError::ReserveLength { expected, found } => {
+                write!(f, "reserve price has {found} bits, expected {expected}")
+            }
+            AuctionError::ReserveBit { bit } => {
+                write!(f, "reserve price has improper ciphertext at bit {bit}")
+            }
+            AuctionError::InvalidPriority => {
+                write!(f, "tie break priority is not a permutation of bidders")
+            }
+            AuctionError::InvalidSlots {
+                slots,
+                bidder_count,
+            } => write!(f, "invalid no. of slots {slots} for {bidder_count} bidders"),
+            AuctionError::ZeroUnits => write!(f, "auction must sell at least one unit"),
+            AuctionError::ThreadPool(e) => write!(f, "failed to build thread pool: {e}"),
+            AuctionError::Unsupported(e) => write!(f, "unsupported: {e}"),
+            AuctionError::Serialize(e) => write!(f, "failed to serialize ciphertext: {e}"),
+            AuctionError::Oracle(e) => write!(f, "decryption oracle failed: {e}"),
+            AuctionError::Gate(e) => write!(f, "gate evaluation failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AuctionError {}
+
+/// Errors returned when reading or writing keys, bids and results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// Reading or writing failed
+    Io(String),
+    /// Data does not start with the format magic
+    Magic,
+    /// Format version is not supported
+    Version(u16),
+    UnknownPayloadKind(u8),
+    UnknownParameterSet(u16),
+    UnknownBitOrder(u8),
+    /// Payload is of a different kind than requested
+    PayloadKind {
+        expected: PayloadKind,
+        found: PayloadKind,
+    },
+    /// Payload is stored under a different parameter set than requested
+    ParameterSet {
+        expected: ParameterSet,
+        found: ParameterSet,
+    },
+    /// No. of bits does not match bit width in header
+    BitWidth {
+        expected: usize,
+        found: usize,
+    },
+    /// No. of bidder ids does not match no. of winner bits
+    BidderCount {
+        expected: usize,
+        found: usize,
+    },
+    /// Bid header has no bidder id
+    MissingBidder,
+    /// Bidder id is reserved for headers without a bidder
+    ReservedBidder,
+    /// Bit width does not fit in the header
+    BitWidthOverflow(usize),
+    /// Payload could not be encoded or decoded
+    Payload(String),
+}
+
+impl FormatError {
+    pub(crate) fn io<E: fmt::Display>(e: E) -> Self {
+        FormatError::Io(e.to_string())
+    }
+
+    pub(crate) fn payload<E: fmt::Display>(e: E) -> Self {
+        FormatError::Payload(e.to_string())
+    }
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Io(e) => write!(f, "i/o failed: {e}"),
+            FormatError::Magic => write!(f, "not an auction file"),
+            FormatError::Version(v) => write!(f, "unsupported format version {v}"),
+            FormatError::UnknownPayloadKind(k) => write!(f, "unknown payload kind {k}"),
+            FormatError::UnknownParameterSet(p) => write!(f, "unknown parameter set {p}"),
+            FormatError::UnknownBitOrder(b) => write!(f, "unknown bit order {b}"),
+            FormatError::PayloadKind { expected, found } => {
+                write!(f, "expected {expected:?}, found {found:?}")
+            }
+            FormatError::ParameterSet { expected, found } => {
+                write!(f, "expected parameter set {expected:?}, found {found:?}")
+            }
+            FormatError::BitWidth { expected, found } => {
+                write!(f, "expected {expected} bits, found {found}")
+            }
+            FormatError::BidderCount { expected, found } => {
+                write!(f, "expected {expected} bidder ids, found {found}")
+            }
+            FormatError::MissingBidder => write!(f, "bid has no bidder id"),
+            FormatError::ReservedBidder => write!(f, "bidder id {} is reserved", u32::MAX),
+            FormatError::BitWidthOverflow(w) => write!(f, "bit width {w} does not fit in header"),
+            FormatError::Payload(e) => write!(f, "invalid payload: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
@@ -0,0 +1,533 @@
+//! Versioned on-disk and wire format of keys, encrypted bids and auction results.
+//!
+//! Every file starts with a fixed size header followed by the bincode encoded
+//! payload:
+//!
+//! | bytes | field                                        |
+//! |-------|----------------------------------------------|
+//! | 4     | magic `FHEA`                                 |
+//! | 2     | format version, little endian                |
+//! | 1     | payload kind                                 |
+//! | 2     | parameter set id, little endian              |
+//! | 1     | bit order, 0 for MSB first                   |
+//! | 4     | bit width, little endian                     |
+//! | 4     | bidder id, little endian, `u32::MAX` if none |
+//!
+//! Header is read before the payload, thus files of another version or
+//! parameter set are rejected without decoding the payload. Parameter set and
+//! payload kind follow from the [Payload] and [Key] types read or written.
+use std::io::{Read, Write};
+
+use serde::{de::DeserializeOwned, Serialize};
+use tfhe::{boolean, gadget};
+
+use crate::{AuctionResult, FormatError};
+
+pub const MAGIC: [u8; 4] = *b"FHEA";
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Bidder id of headers without a bidder, thus not a valid bidder id
+pub const NO_BIDDER: u32 = u32::MAX;
+
+/// Parameter set keys and ciphertexts are generated under
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ParameterSet {
+    /// `tfhe::gadget::boolean::BOOLEAN_PARAMETERS`
+    #[default]
+    GadgetBoolean,
+    /// Default parameters of upstream `tfhe::boolean`
+    Boolean,
+}
+
+/// Order of bits of an amount
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    #[default]
+    MsbFirst,
+    LsbFirst,
+}
+
+/// What follows the header
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PayloadKind {
+    ClientKey,
+    ServerKey,
+    Bid,
+    AuctionResult,
+    PublicKey,
+}
+
+/// Key or ciphertext stored under a parameter set
+pub trait Payload: Serialize + DeserializeOwned {
+    const PARAMETER_SET: ParameterSet;
+}
+
+/// Key stored on its own
+pub trait Key: Payload {
+    const KIND: PayloadKind;
+}
+
+impl Payload for gadget::client_key::ClientKey {
+    const PARAMETER_SET: ParameterSet = ParameterSet::GadgetBoolean;
+}
+
+impl Payload for gadget::server_key::ServerKey {
+    const PARAMETER_SET: ParameterSet = ParameterSet::GadgetBoolean;
+}
+
+impl Payload for gadget::ciphertext::Ciphertext {
+    const PARAMETER_SET: ParameterSet = ParameterSet::GadgetBoolean;
+}
+
+impl Payload for boolean::client_key::ClientKey {
+    const PARAMETER_SET: ParameterSet = ParameterSet::Boolean;
+}
+
+impl Payload for boolean::server_key::ServerKey {
+    const PARAMETER_SET: ParameterSet = ParameterSet::Boolean;
+}
+
+impl Payload for boolean::public_key::PublicKey {
+    const PARAMETER_SET: ParameterSet = ParameterSet::Boolean;
+}
+
+impl Payload for boolean::ciphertext::Ciphertext {
+    const PARAMETER_SET: ParameterSet = ParameterSet::Boolean;
+}
+
+impl Key for gadget::client_key::ClientKey {
+    const KIND: PayloadKind = PayloadKind::ClientKey;
+}
+
+impl Key for gadget::server_key::ServerKey {
+    const KIND: PayloadKind = PayloadKind::ServerKey;
+}
+
+impl Key for boolean::client_key::ClientKey {
+    const KIND: PayloadKind = PayloadKind::ClientKey;
+}
+
+impl Key for boolean::server_key::ServerKey {
+    const KIND: PayloadKind = PayloadKind::ServerKey;
+}
+
+impl Key for boolean::public_key::PublicKey {
+    const KIND: PayloadKind = PayloadKind::PublicKey;
+}
+
+/// Header preceding every payload
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub version: u16,
+    pub kind: PayloadKind,
+    pub parameter_set: ParameterSet,
+    pub bit_order: BitOrder,
+    /// Bits per bid, or of the amount for auction results. 0 for keys.
+    pub bit_width: u32,
+    pub bidder: Option<u32>,
+}
+
+impl ParameterSet {
+    fn id(self) -> u16 {
+        match self {
+            ParameterSet::GadgetBoolean => 1,
+            ParameterSet::Boolean => 2,
+        }
+    }
+
+    fn from_id(id: u16) -> Result<Self, FormatError> {
+        match id {
+            1 => Ok(ParameterSet::GadgetBoolean),
+            2 => Ok(ParameterSet::Boolean),
+            _ => Err(FormatError::UnknownParameterSet(id)),
+        }
+    }
+}
+
+impl PayloadKind {
+    fn id(self) -> u8 {
+        match self {
+            PayloadKind::ClientKey => 1,
+            PayloadKind::ServerKey => 2,
+            PayloadKind::Bid => 3,
+            PayloadKind::AuctionResult => 4,
+            PayloadKind::PublicKey => 5,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, FormatError> {
+        match id {
+            1 => Ok(PayloadKind::ClientKey),
+            2 => Ok(PayloadKind::ServerKey),
+            3 => Ok(PayloadKind::Bid),
+            4 => Ok(PayloadKind::AuctionResult),
+            5 => Ok(PayloadKind::PublicKey),
+            _ => Err(FormatError::UnknownPayloadKind(id)),
+        }
+    }
+}
+
+impl Header {
+    fn new(
+        kind: PayloadKind,
+        parameter_set: ParameterSet,
+        bit_width: usize,
+        bidder: Option<u32>,
+    ) -> Result<Self, FormatError> {
+        if bidder == Some(NO_BIDDER) {
+            return Err(FormatError::ReservedBidder);
+        }
+        Ok(Header {
+            version: FORMAT_VERSION,
+            kind,
+            parameter_set,
+            bit_order: BitOrder::MsbFirst,
+            bit_width: u32::try_from(bit_width)
+                .map_err(|_| FormatError::BitWidthOverflow(bit_width))?,
+            bidder,
+        })
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> Result<(), FormatError> {
+        let bit_order = match self.bit_order {
+            BitOrder::MsbFirst => 0u8,
+            BitOrder::LsbFirst => 1u8,
+        };
+        let mut bytes = Vec::with_capacity(18);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&self.version.to_le_bytes());
+        bytes.push(self.kind.id());
+        bytes.extend_from_slice(&self.parameter_set.id().to_le_bytes());
+        bytes.push(bit_order);
+        bytes.extend_from_slice(&self.bit_width.to_le_bytes());
+        bytes.extend_from_slice(&self.bidder.unwrap_or(NO_BIDDER).to_le_bytes());
+        writer.write_all(&bytes).map_err(FormatError::io)
+    }
+
+    /// Reads header and checks it is of a supported version
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self, FormatError> {
+        let mut bytes = [0u8; 18];
+        reader.read_exact(&mut bytes).map_err(FormatError::io)?;
+        if bytes[0..4] != MAGIC {
+            return Err(FormatError::Magic);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != FORMAT_VERSION {
+            return Err(FormatError::Version(version));
+        }
+        let bit_order = match bytes[9] {
+            0 => BitOrder::MsbFirst,
+            1 => BitOrder::LsbFirst,
+            b => return Err(FormatError::UnknownBitOrder(b)),
+        };
+        let bidder = u32::from_le_bytes([bytes[14], bytes[15], bytes[16], bytes[17]]);
+
+        Ok(Header {
+            version,
+            kind: PayloadKind::from_id(bytes[6])?,
+            parameter_set: ParameterSet::from_id(u16::from_le_bytes([bytes[7], bytes[8]]))?,
+            bit_order,
+            bit_width: u32::from_le_bytes([bytes[10], bytes[11], bytes[12], bytes[13]]),
+            bidder: (bidder != NO_BIDDER).then_some(bidder),
+        })
+    }
+
+    fn expect(self, kind: PayloadKind, parameter_set: ParameterSet) -> Result<Self, FormatError> {
+        if self.kind != kind {
+            return Err(FormatError::PayloadKind {
+                expected: kind,
+                found: self.kind,
+            });
+        }
+        if self.parameter_set != parameter_set {
+            return Err(FormatError::ParameterSet {
+                expected: parameter_set,
+                found: self.parameter_set,
+            });
+        }
+        Ok(self)
+    }
+}
+
+fn write_payload<W: Write, T: Serialize + ?Sized>(
+    writer: &mut W,
+    header: Header,
+    payload: &T,
+) -> Result<(), FormatError> {
+    header.write(writer)?;
+    bincode::serialize_into(writer, payload).map_err(FormatError::payload)
+}
+
+fn read_payload<R: Read, T: DeserializeOwned>(
+    reader: &mut R,
+    kind: PayloadKind,
+    parameter_set: ParameterSet,
+) -> Result<(Header, T), FormatError> {
+    let header = Header::read(reader)?.expect(kind, parameter_set)?;
+    let payload = bincode::deserialize_from(reader).map_err(FormatError::payload)?;
+    Ok((header, payload))
+}
+
+/// Returns `bits` from MSB to LSB, checking there are `bit_width` of them
+fn msb_first<T>(header: &Header, mut bits: Vec<T>) -> Result<Vec<T>, FormatError> {
+    if bits.len() != header.bit_width as usize {
+        return Err(FormatError::BitWidth {
+            expected: header.bit_width as usize,
+            found: bits.len(),
+        });
+    }
+    if header.bit_order == BitOrder::LsbFirst {
+        bits.reverse();
+    }
+    Ok(bits)
+}
+
+pub fn write_key<W: Write, K: Key>(writer: &mut W, key: &K) -> Result<(), FormatError> {
+    write_payload(
+        writer,
+        Header::new(K::KIND, K::PARAMETER_SET, 0, None)?,
+        key,
+    )
+}
+
+pub fn read_key<R: Read, K: Key>(reader: &mut R) -> Result<K, FormatError> {
+    read_payload(reader, K::KIND, K::PARAMETER_SET).map(|(_, key)| key)
+}
+
+/// Writes encrypted bid of `bidder`, bits from MSB to LSB. `bidder` must not
+/// be [NO_BIDDER].
+pub fn write_bid<W: Write, C: Payload>(
+    writer: &mut W,
+    bidder: u32,
+    bid: &[C],
+) -> Result<(), FormatError> {
+    let header = Header::new(PayloadKind::Bid, C::PARAMETER_SET, bid.len(), Some(bidder))?;
+    write_payload(writer, header, bid)
+}
+
+/// Reads encrypted bid and returns the bidder id and bits from MSB to LSB
+pub fn read_bid<R: Read, C: Payload>(reader: &mut R) -> Result<(u32, Vec<C>), FormatError> {
+    let (header, bid) = read_payload(reader, PayloadKind::Bid, C::PARAMETER_SET)?;
+    let bidder = header.bidder.ok_or(FormatError::MissingBidder)?;
+    Ok((bidder, msb_first(&header, bid)?))
+}
+
+/// Writes auction `result`, where `bidders[j]` is the id of bidder at index j of
+/// the winner vector
+pub fn write_result<W: Write, C: Payload>(
+    writer: &mut W,
+    bidders: &[u32],
+    result: &AuctionResult<C>,
+) -> Result<(), FormatError> {
+    let header = Header::new(
+        PayloadKind::AuctionResult,
+        C::PARAMETER_SET,
+        result.amount.len(),
+        None,
+    )?;
+    let payload = (
+        bidders,
+        &result.winners,
+        &result.amount,
+        &result.reserve_met,
+    );
+    write_payload(writer, header, &payload)
+}
+
+/// Reads auction result and returns bidder ids along with the result
+pub fn read_result<R: Read, C: Payload>(
+    reader: &mut R,
+) -> Result<(Vec<u32>, AuctionResult<C>), FormatError> {
+    let (header, (bidders, winners, amount, reserve_met)) =
+        read_payload::<_, (Vec<u32>, Vec<C>, Vec<C>, Option<C>)>(
+            reader,
+            PayloadKind::AuctionResult,
+            C::PARAMETER_SET,
+        )?;
+
+    if bidders.len() != winners.len() {
+        return Err(FormatError::BidderCount {
+            expected: winners.len(),
+            found: bidders.len(),
+        });
+    }
+
+    let result = AuctionResult {
+        winners,
+        amount: msb_first(&header, amount)?,
+        reserve_met,
+    };
+    Ok((bidders, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{
+        boolean::BOOLEAN_PARAMETERS, ciphertext::Ciphertext, client_key::ClientKey, gen_keys,
+        server_key::ServerKey,
+    };
+
+    use super::*;
+    use crate::{
+        test_utils::{decrypt_amount, decrypt_indices, encrypt_bid},
+        Auction, BidEncryptor,
+    };
+
+    #[test]
+    fn keys_and_bids_round_trip() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+
+        let mut bytes = vec![];
+        write_key(&mut bytes, &client_key)?;
+        let client_key: ClientKey = read_key(&mut bytes.as_slice())?;
+
+        let mut bytes = vec![];
+        write_key(&mut bytes, &server_key)?;
+        let server_key: ServerKey = read_key(&mut bytes.as_slice())?;
+
+        let mut files = vec![];
+        for (bidder, amount) in [(7, 90), (3, 200), (12, 41)] {
+            let mut bytes = vec![];
+            write_bid(
+                &mut bytes,
+                bidder,
+                &encrypt_bid(&client_key, amount, bid_bits),
+            )?;
+            files.push(bytes);
+        }
+
+        let header = Header::read(&mut files[1].as_slice())?;
+        assert_eq!(
+            header,
+            Header {
+                version: FORMAT_VERSION,
+                kind: PayloadKind::Bid,
+                parameter_set: ParameterSet::GadgetBoolean,
+                bit_order: BitOrder::MsbFirst,
+                bit_width: 8,
+                bidder: Some(3),
+            }
+        );
+
+        let (bidders, bids): (Vec<_>, Vec<_>) = files
+            .iter()
+            .map(|bytes| read_bid::<_, Ciphertext>(&mut bytes.as_slice()))
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+        assert_eq!(bidders, vec![7, 3, 12]);
+        assert_eq!(decrypt_amount(&client_key, &bids[1]), 200);
+
+        // keys and bids still work after a round trip
+        let res = Auction::new(&server_key, bid_bits).run(&bids)?;
+        let mut bytes = vec![];
+        write_result(&mut bytes, &bidders, &res)?;
+        let (read_bidders, res) = read_result::<_, Ciphertext>(&mut bytes.as_slice())?;
+        assert_eq!(read_bidders, bidders);
+        assert_eq!(decrypt_indices(&client_key, &res.winners), vec![1]);
+        assert_eq!(decrypt_amount(&client_key, &res.amount), 200);
+        assert!(res.reserve_met.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_headers() -> Result<(), Box<dyn std::error::Error>> {
+        let (client_key, _) = gen_keys(&BOOLEAN_PARAMETERS);
+        let mut bid = vec![];
+        write_bid(&mut bid, 0, &encrypt_bid(&client_key, 5, 4))?;
+
+        assert_eq!(
+            read_key::<_, ServerKey>(&mut bid.as_slice()).err(),
+            Some(FormatError::PayloadKind {
+                expected: PayloadKind::ServerKey,
+                found: PayloadKind::Bid
+            })
+        );
+
+        let mut bytes = bid.clone();
+        bytes[0] = b'X';
+        assert_eq!(
+            read_bid::<_, Ciphertext>(&mut bytes.as_slice()).err(),
+            Some(FormatError::Magic)
+        );
+
+        let mut bytes = bid.clone();
+        bytes[4] = 2;
+        assert_eq!(
+            read_bid::<_, Ciphertext>(&mut bytes.as_slice()).err(),
+            Some(FormatError::Version(2))
+        );
+
+        let mut bytes = bid.clone();
+        bytes[7] = 9;
+        assert_eq!(
+            read_bid::<_, Ciphertext>(&mut bytes.as_slice()).err(),
+            Some(FormatError::UnknownParameterSet(9))
+        );
+
+        // LSB first bids are reordered
+        let mut bytes = bid.clone();
+        bytes[9] = 1;
+        let (_, bits) = read_bid::<_, Ciphertext>(&mut bytes.as_slice())?;
+        assert_eq!(decrypt_amount(&client_key, &bits), 0b1010);
+
+        assert!(matches!(
+            read_bid::<_, Ciphertext>(&mut &bid[..10]),
+            Err(FormatError::Io(_))
+        ));
+
+        // bidder id of headers without a bidder is never written
+        assert_eq!(
+            write_bid(&mut vec![], NO_BIDDER, &encrypt_bid(&client_key, 5, 4)).err(),
+            Some(FormatError::ReservedBidder)
+        );
+        let bit_width = u32::MAX as usize + 1;
+        assert_eq!(
+            Header::new(
+                PayloadKind::Bid,
+                ParameterSet::GadgetBoolean,
+                bit_width,
+                Some(0)
+            )
+            .err(),
+            Some(FormatError::BitWidthOverflow(bit_width))
+        );
+
+        // gadget bids are not read as upstream boolean bids
+        assert_eq!(
+            read_bid::<_, boolean::ciphertext::Ciphertext>(&mut bid.as_slice()).err(),
+            Some(FormatError::ParameterSet {
+                expected: ParameterSet::Boolean,
+                found: ParameterSet::GadgetBoolean
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn public_key_round_trips() -> Result<(), Box<dyn std::error::Error>> {
+        let (client_key, _) = boolean::gen_keys();
+        let public_key = boolean::public_key::PublicKey::new(&client_key);
+
+        let mut bytes = vec![];
+        write_key(&mut bytes, &public_key)?;
+        let header = Header::read(&mut bytes.as_slice())?;
+        assert_eq!(header.kind, PayloadKind::PublicKey);
+        assert_eq!(header.parameter_set, ParameterSet::Boolean);
+        let public_key: boolean::public_key::PublicKey = read_key(&mut bytes.as_slice())?;
+
+        let mut bytes = vec![];
+        write_bid(&mut bytes, 4, &public_key.encrypt_bid(9, 4)?)?;
+        let (bidder, bid) = read_bid::<_, boolean::ciphertext::Ciphertext>(&mut bytes.as_slice())?;
+        assert_eq!(bidder, 4);
+        let amount = bid
+            .iter()
+            .fold(0u64, |acc, ct| (acc << 1) | client_key.decrypt(ct) as u64);
+        assert_eq!(amount, 9);
+
+        Ok(())
+    }
+}
@@ -1,47 +1,99 @@
-use tfhe::gadget::{ciphertext::Ciphertext, server_key::ServerKey};
+use mux::mux;
+use parallel::try_map;
+use reduce::or_reduce;
 
-fn auction_circuit(
-    server_key: &ServerKey,
-    bids: &Vec<Vec<Ciphertext>>,
+mod auction;
+mod builder;
+mod cost;
+#[cfg(test)]
+mod differential;
+mod early_decrypt;
+mod encrypt;
+mod engine;
+mod error;
+mod format;
+mod multi_unit;
+mod mux;
+mod parallel;
+mod reduce;
+mod reserve;
+#[cfg(test)]
+mod test_utils;
+mod tie_break;
+mod top_k;
+mod validate;
+mod vickrey;
+
+pub use auction::{Auction, AuctionResult, Direction, Pricing};
+pub use builder::{CircuitBuilder, CircuitOptions};
+pub use cost::{CostModel, CostReport, Counted, CountingEngine, Estimate};
+pub use early_decrypt::{DecryptionOracle, EarlyDecryptResult};
+pub use encrypt::BidEncryptor;
+pub use engine::{BidBit, BooleanEngine, PlaintextEngine};
+pub use error::{AuctionError, FormatError};
+pub use format::{
+    read_bid, read_key, read_result, write_bid, write_key, write_result, BitOrder, Header, Key,
+    ParameterSet, Payload, PayloadKind, FORMAT_VERSION, MAGIC, NO_BIDDER,
+};
+pub use multi_unit::{MultiUnit, MultiUnitPricing, MultiUnitResult, Payments};
+pub use mux::Multiplexer;
+pub use reduce::OrReduction;
+pub use tie_break::TieBreak;
+pub use top_k::{TopK, TopKResult};
+pub use validate::{validate_bid, validate_bids};
+
+/// Winner vector and amount bits from MSB to LSB
+pub(crate) type Selection<C> = (Vec<C>, Vec<C>);
+
+pub(crate) fn auction_circuit<E: BooleanEngine>(
+    server_key: &E,
+    bids: &[Vec<E::Ciphertext>],
     bid_bits: usize,
     bidder_count: usize,
-) -> Result<(Vec<Ciphertext>, Vec<Ciphertext>), Box<dyn std::error::Error>> {
-    //TODO: check bids are correctly formed
+    options: CircuitOptions,
+) -> Result<Selection<E::Ciphertext>, AuctionError> {
+    validate_bids(bids, bid_bits, bidder_count)?;
 
-    let mut w = vec![Ciphertext::Trivial(true); bidder_count];
-    let mut s = vec![Ciphertext::Placeholder; bidder_count];
-    let mut amount = vec![Ciphertext::Placeholder; bid_bits];
+    bit_slice_max(
+        server_key,
+        bids,
+        bid_bits,
+        vec![server_key.trivial(true); bidder_count],
+        options,
+    )
+}
+
+/// Bit-slice max over bids of bidders with `w[j] = 1`.
+///
+/// Returns updated `w` with `w[j] = 1` iff j^th bidder is among the highest of
+/// the initially selected bidders and the highest amount bits from MSB to LSB.
+/// If no bidder is selected the amount equals 0.
+pub(crate) fn bit_slice_max<E: BooleanEngine>(
+    server_key: &E,
+    bids: &[Vec<E::Ciphertext>],
+    bid_bits: usize,
+    mut w: Vec<E::Ciphertext>,
+    options: CircuitOptions,
+) -> Result<Selection<E::Ciphertext>, AuctionError> {
+    let mut amount = vec![server_key.trivial(false); bid_bits];
     for i in 0..bid_bits {
-        // let now = std::time::Instant::now();
-        for j in 0..bidder_count {
-            // AND at i^th MSB of j^th bidder
-            s[j] = server_key.and(&w[j], &bids[j][i])?;
-        }
-
-        // OR
-        let b = {
-            let mut b = server_key.or(&s[0], &s[1])?;
-            for j in 2..bidder_count {
-                b = server_key.or(&b, &s[j])?;
-            }
-            b
-        };
+        // AND at i^th MSB of j^th bidder
+        let s = try_map(&w, |j, w_j| server_key.and(w_j, &bids[j][i]))?;
+
+        // OR. With a single bidder `b` is simply s[0]
+        let b = or_reduce(server_key, s.clone(), options.or_reduction)?;
 
         //  We require a multiplexer here and there are few ways to implement it:
         // 1. Circuit bootstrapping: Circuit bootstrap $b$ to a GGSW ciphertext and then use a single CMUX operation. However circuit bootstrapping itself requires $pbslevel$  bootstrapping operations + $pbslevel$ LWE -> RLWE key switching operations. Moreover, it requires private functional key switching keys. I don't think circuit bootstrapping improves runtime significantly such that it is worth it deal with its complexity + introducing more keys.
         // 2. Switch to 7-encoding space: In 7-encoding space this operation can be evaluated as single bootstrapping operation. However, p=7 requires 3 bit plaintext space thus doubling the bootstrapping runtime as compared to 2 bit plaintext space. Let bootstrapping runtime with 2-bit plaintext be x. Then evaluating AND + OR + MULTIPLEXER (MULTIPLEXER = $bs$ + $!bw$) takes 5x. With 3-bit plaintext space bootstrapping runtime equals 2x. Evaluating AND + OR + MULTIPLEXER (MULTIPLEXER is a single bootstrap) takes 5x. Thus, there's no benefit of switching to 7-encoding space.
         // 3. Rewriting multiplexer as $b * (s - w) + w$: This assumes ciphertexts are in canonical encoding (i.e. either 0/1 instead of 1/2). Switching from 1/2 to 0/1 is trivial since it requires a single plaintext subtraction by 1. However,  $s - w$ may equal -1 which will equal 2 in modulus 3. This forces lookup table to output to different values at same input (input: 1,0), which isn't possible.
         // 4. Naively implementation the multiplexer as $b s || !bw$: We implement this for now. However this requires 3 bootstrapping operations causing this to be the most expensive part of the circuit.
-        // 5. Decrypting $b$: Since $b$ has to decrypted anyways to learn amount (assuming highest price auction), decrypting it before evaluating the multiplexer can save us from implementation it.
-        // AND to reset w
-        let b_not = server_key.not(&b);
-        for j in 0..bidder_count {
-            // (b & s[j]) + (!b & w[j])
-            let c0 = server_key.and(&b, &s[j])?;
-            let c1 = server_key.and(&b_not, &w[j])?;
-            w[j] = server_key.or(&c0, &c1)?;
-        }
-        // println!("Time i:{i} - {}", now.elapsed().as_millis());
+        // 5. Decrypting $b$: Since $b$ has to decrypted anyways to learn amount (assuming highest price auction), decrypting it before evaluating the multiplexer can save us from implementation it. Implemented in `Auction::run_early_decrypt`.
+        // Option 4 is the default `Multiplexer::ThreeGate`, option 2 is `Multiplexer::SevenEncoding`.
+        // (b & s[j]) + (!b & w[j])
+        w = try_map(&s, |j, s_j| {
+            mux(server_key, &b, s_j, &w[j], options.multiplexer)
+        })?;
         // set i^th MSB of amount
         amount[i] = b;
     }
@@ -49,14 +101,32 @@ fn auction_circuit(
     Ok((w, amount))
 }
 
+/// Complements every bit of `bits`
+pub(crate) fn complement<E: BooleanEngine>(
+    server_key: &E,
+    bits: &[E::Ciphertext],
+) -> Vec<E::Ciphertext> {
+    bits.iter().map(|bit| server_key.not(bit)).collect()
+}
+
+/// ANDs every ciphertext in `bits` with `b`
+pub(crate) fn mask<E: BooleanEngine>(
+    server_key: &E,
+    b: &E::Ciphertext,
+    bits: &[E::Ciphertext],
+) -> Result<Vec<E::Ciphertext>, AuctionError> {
+    try_map(bits, |_, bit| server_key.and(b, bit))
+}
+
 #[cfg(test)]
 mod tests {
     use rand::{thread_rng, Rng};
-    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, ciphertext::Ciphertext, gen_keys};
 
     use super::*;
 
     #[test]
+    #[allow(non_snake_case, clippy::useless_conversion, clippy::assign_op_pattern)]
     fn auction_circuit_works() -> Result<(), Box<dyn std::error::Error>> {
         let bidders = 50;
         let BID_BITS = 64;
@@ -85,8 +155,11 @@ mod tests {
             .collect::<Vec<Vec<Ciphertext>>>();
 
         let now = std::time::Instant::now();
-        let (winner_identity_bit, winning_amount_bits) =
-            auction_circuit(&server_key, &encrypts_bid_vector, BID_BITS, bidders)?;
+        let AuctionResult {
+            winners: winner_identity_bit,
+            amount: winning_amount_bits,
+            ..
+        } = Auction::new(&server_key, BID_BITS).run(&encrypts_bid_vector)?;
         println!("Auction runtime: {}ms", now.elapsed().as_millis());
 
         // find the highest bidder amount
@@ -137,4 +210,48 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn auction_with_single_bidder() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let bid = 0b1011_0010u64;
+
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let encrypted_bid = (0..bid_bits)
+            .map(|i| client_key.encrypt((bid >> (bid_bits - 1 - i)) & 1 != 0))
+            .collect::<Vec<Ciphertext>>();
+
+        let res = Auction::new(&server_key, bid_bits).run(&[encrypted_bid])?;
+
+        let mut amount = 0u64;
+        res.amount.iter().enumerate().for_each(|(index, ct)| {
+            amount += (client_key.decrypt(ct) as u64) << (bid_bits - 1 - index);
+        });
+        assert_eq!(amount, bid);
+        assert_eq!(res.winners.len(), 1);
+        assert!(client_key.decrypt(&res.winners[0]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn auction_rejects_no_bidders() {
+        let res = Auction::new(&PlaintextEngine, 8).run(&[]);
+        assert_eq!(res.err(), Some(AuctionError::NoBidders));
+    }
+
+    #[test]
+    fn auction_rejects_bid_with_wrong_length() {
+        let bids = vec![vec![true; 8], vec![true; 7]];
+
+        let res = Auction::new(&PlaintextEngine, 8).run(&bids);
+        assert_eq!(
+            res.err(),
+            Some(AuctionError::BidLength {
+                bidder: 1,
+                expected: 8,
+                found: 7
+            })
+        );
+    }
 }
@@ -1,3 +1,247 @@
+//! Command-line tool to run sealed-bid auctions.
+//!
+//! Keys, bids and results are stored in the versioned format of
+//! [fhe_auctions::Header]. Bids are encrypted bit by bit from MSB to LSB.
+//!
+//! By default auctions run on the gadget and bids are encrypted with the client
+//! key. With `--backend boolean` auctions run on the upstream [tfhe::boolean]
+//! backend instead and bids are encrypted with a public key, thus bidders never
+//! receive the client key.
+use std::{
+    error::Error,
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use fhe_auctions::{
+    read_bid, read_key, read_result, write_bid, write_key, write_result, Auction, AuctionError,
+    BidEncryptor, BooleanEngine, FormatError, Key, Payload,
+};
+use tfhe::{boolean, gadget};
+
+/// Widest bid the CLI decrypts, since amounts are printed as `u64`
+const MAX_BID_BITS: usize = 64;
+
+const USAGE: &str = "usage:
+  fhe-auctions [--backend gadget|boolean] <command>
+
+commands:
+  keygen <key-dir>
+      writes <key-dir>/client.key and <key-dir>/server.key, with boolean
+      backend also <key-dir>/public.key
+  encrypt-bid <key> <bidder-id> <bid-bits> <amount> <bid-file>
+      encrypts <amount> as <bid-bits> bits from MSB to LSB, at most 64 bits.
+      <key> is the client key, with boolean backend the public key
+  run-auction <server-key> <bid-dir> <result-file>
+      runs first price auction over every file in <bid-dir>, bidder ids must
+      be unique
+  decrypt-result <client-key> <result-file>
+      prints winners and winning amount
+
+backends:
+  gadget   p-encoding gates of the gadget, bids are encrypted with the client
+           key (default)
+  boolean  upstream tfhe::boolean gates, bids are encrypted with the public key";
+
+/// Parameter set keys, bids and results are generated under
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Backend {
+    Gadget,
+    Boolean,
+}
+
 fn main() {
-    println!("Hello, world!");
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let (backend, args) = match args.as_slice() {
+        ["--backend", "gadget", args @ ..] => (Backend::Gadget, args),
+        ["--backend", "boolean", args @ ..] => (Backend::Boolean, args),
+        ["--backend", ..] => usage(),
+        args => (Backend::Gadget, args),
+    };
+
+    if let Err(e) = run(backend, args) {
+        eprintln!("error: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run(backend: Backend, args: &[&str]) -> Result<(), Box<dyn Error>> {
+    match (backend, args) {
+        (_, ["keygen", key_dir]) => keygen(backend, Path::new(key_dir)),
+        (Backend::Gadget, ["encrypt-bid", key, bidder, bid_bits, amount, bid_file]) => {
+            encrypt_bid::<gadget::client_key::ClientKey>(
+                Path::new(key),
+                bidder,
+                bid_bits,
+                amount,
+                Path::new(bid_file),
+            )
+        }
+        (Backend::Boolean, ["encrypt-bid", key, bidder, bid_bits, amount, bid_file]) => {
+            encrypt_bid::<boolean::public_key::PublicKey>(
+                Path::new(key),
+                bidder,
+                bid_bits,
+                amount,
+                Path::new(bid_file),
+            )
+        }
+        (Backend::Gadget, ["run-auction", server_key, bid_dir, result_file]) => {
+            run_auction::<gadget::server_key::ServerKey>(
+                Path::new(server_key),
+                Path::new(bid_dir),
+                Path::new(result_file),
+            )
+        }
+        (Backend::Boolean, ["run-auction", server_key, bid_dir, result_file]) => {
+            run_auction::<boolean::server_key::ServerKey>(
+                Path::new(server_key),
+                Path::new(bid_dir),
+                Path::new(result_file),
+            )
+        }
+        (Backend::Gadget, ["decrypt-result", client_key, result_file]) => {
+            let client_key: gadget::client_key::ClientKey =
+                read_key(&mut open(Path::new(client_key))?)?;
+            decrypt_result(Path::new(result_file), |ct| client_key.decrypt(ct))
+        }
+        (Backend::Boolean, ["decrypt-result", client_key, result_file]) => {
+            let client_key: boolean::client_key::ClientKey =
+                read_key(&mut open(Path::new(client_key))?)?;
+            decrypt_result(Path::new(result_file), |ct| client_key.decrypt(ct))
+        }
+        _ => usage(),
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    std::process::exit(2);
+}
+
+fn keygen(backend: Backend, key_dir: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(key_dir)?;
+    match backend {
+        Backend::Gadget => {
+            let (client_key, server_key) = gadget::gen_keys(&gadget::boolean::BOOLEAN_PARAMETERS);
+            write_file(&key_dir.join("client.key"), |w| write_key(w, &client_key))?;
+            write_file(&key_dir.join("server.key"), |w| write_key(w, &server_key))?;
+        }
+        Backend::Boolean => {
+            let (client_key, server_key) = boolean::gen_keys();
+            let public_key = boolean::public_key::PublicKey::new(&client_key);
+            write_file(&key_dir.join("client.key"), |w| write_key(w, &client_key))?;
+            write_file(&key_dir.join("server.key"), |w| write_key(w, &server_key))?;
+            write_file(&key_dir.join("public.key"), |w| write_key(w, &public_key))?;
+        }
+    }
+    println!("wrote keys to {}", key_dir.display());
+    Ok(())
+}
+
+fn encrypt_bid<K>(
+    key: &Path,
+    bidder: &str,
+    bid_bits: &str,
+    amount: &str,
+    bid_file: &Path,
+) -> Result<(), Box<dyn Error>>
+where
+    K: Key + BidEncryptor,
+    K::Ciphertext: Payload,
+{
+    let bidder = bidder.parse::<u32>()?;
+    let bid_bits = bid_bits.parse::<usize>()?;
+    if bid_bits > MAX_BID_BITS {
+        return Err(format!("bid bits {bid_bits} exceed {MAX_BID_BITS}").into());
+    }
+    let amount = amount.parse::<u64>()?;
+
+    let key: K = read_key(&mut open(key)?)?;
+    let bid = key.encrypt_bid(amount, bid_bits)?;
+    write_file(bid_file, |w| write_bid(w, bidder, &bid))?;
+    Ok(())
+}
+
+fn run_auction<S>(
+    server_key: &Path,
+    bid_dir: &Path,
+    result_file: &Path,
+) -> Result<(), Box<dyn Error>>
+where
+    S: Key + BooleanEngine,
+    S::Ciphertext: Payload,
+{
+    let server_key: S = read_key(&mut open(server_key)?)?;
+
+    let mut bid_files = fs::read_dir(bid_dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    bid_files.retain(|path| path.is_file());
+
+    let mut bids = bid_files
+        .iter()
+        .map(|path| read_bid::<_, S::Ciphertext>(&mut open(path)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    // bidders are indexed in order of their ids
+    bids.sort_by_key(|(bidder, _)| *bidder);
+    if let Some(pair) = bids.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(format!("bidder {} submitted more than one bid", pair[0].0).into());
+    }
+    let (bidders, bids): (Vec<_>, Vec<_>) = bids.into_iter().unzip();
+    println!("bidders: {bidders:?}");
+
+    // all bids must have as many bits as the first, which auction validates
+    let bid_bits = bids.first().map(Vec::len).ok_or(AuctionError::NoBidders)?;
+    let res = Auction::new(&server_key, bid_bits).run(&bids)?;
+    write_file(result_file, |w| write_result(w, &bidders, &res))?;
+    Ok(())
+}
+
+fn decrypt_result<C, F>(result_file: &Path, decrypt: F) -> Result<(), Box<dyn Error>>
+where
+    C: Payload,
+    F: Fn(&C) -> bool,
+{
+    let (bidders, res) = read_result::<_, C>(&mut open(result_file)?)?;
+    if res.amount.len() > MAX_BID_BITS {
+        return Err(format!(
+            "amount has {} bits, at most {MAX_BID_BITS} are supported",
+            res.amount.len()
+        )
+        .into());
+    }
+
+    for (j, w_j) in res.winners.iter().enumerate() {
+        if decrypt(w_j) {
+            println!("winner: bidder {}", bidders[j]);
+        }
+    }
+    let amount = res
+        .amount
+        .iter()
+        .fold(0u64, |acc, ct| (acc << 1) | decrypt(ct) as u64);
+    println!("amount: {amount}");
+    Ok(())
+}
+
+/// Writes `path` with `write` and flushes it, thus a failed write never leaves
+/// a truncated file behind silently
+fn write_file<F>(path: &Path, write: F) -> Result<(), FormatError>
+where
+    F: FnOnce(&mut BufWriter<File>) -> Result<(), FormatError>,
+{
+    let io_error = |e: std::io::Error| FormatError::Io(format!("{}: {e}", path.display()));
+    let mut writer = File::create(path).map(BufWriter::new).map_err(io_error)?;
+    write(&mut writer)?;
+    writer.flush().map_err(io_error)
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, FormatError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| FormatError::Io(format!("{}: {e}", path.display())))
 }
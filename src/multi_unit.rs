@@ -0,0 +1,278 @@
+use tfhe::gadget::{ciphertext::Ciphertext, server_key::ServerKey};
+
+use crate::{
+    cost::CountingEngine,
+    mask,
+    parallel::{install, try_map},
+    top_k::top_k_circuit,
+    validate_bids, AuctionError, BooleanEngine, CircuitBuilder, CircuitOptions, Direction,
+    PlaintextEngine,
+};
+
+/// Multi-unit auction of `units` identical units over encrypted bids.
+///
+/// Each bidder demands a single unit and the `units` best bidders win. Ties at
+/// the boundary are broken towards the lowest index.
+///
+/// Each bid must be `bid_bits` encrypted bits ordered from MSB to LSB.
+pub struct MultiUnit<'a, E: BooleanEngine = ServerKey> {
+    server_key: &'a E,
+    bid_bits: usize,
+    units: usize,
+    direction: Direction,
+    pricing: MultiUnitPricing,
+    options: CircuitOptions,
+}
+
+/// Amount paid by winners of a multi-unit auction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MultiUnitPricing {
+    /// Every winner pays the clearing price, i.e. the (units + 1)^th best bid
+    #[default]
+    Uniform,
+    /// Every winner pays their own bid (discriminatory auction)
+    PayAsBid,
+}
+
+/// Encrypted payments of a multi-unit auction, amounts as bits from MSB to LSB
+#[derive(Clone)]
+pub enum Payments<C = Ciphertext> {
+    /// Clearing price paid by every winner. If there are no more bidders than
+    /// units, it equals the worst possible bid, i.e. 0 for forward auctions and
+    /// 2^k - 1 for reverse auctions.
+    Uniform(Vec<C>),
+    /// `payments[j]` is the bid of j^th bidder if they win, otherwise 0. Thus
+    /// losing bids are never decrypted.
+    PayAsBid(Vec<Vec<C>>),
+}
+
+/// Encrypted output of a multi-unit auction
+#[derive(Clone)]
+pub struct MultiUnitResult<C = Ciphertext> {
+    /// `winners[j]` encrypts 1 iff j^th bidder wins a unit
+    pub winners: Vec<C>,
+    pub payments: Payments<C>,
+}
+
+impl<'a, E: BooleanEngine> CircuitBuilder for MultiUnit<'a, E> {
+    fn options(&self) -> &CircuitOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut CircuitOptions {
+        &mut self.options
+    }
+
+    fn count_gates(
+        &self,
+        engine: &CountingEngine<PlaintextEngine>,
+        bidder_count: usize,
+        options: CircuitOptions,
+    ) -> Result<(), AuctionError> {
+        let multi_unit = MultiUnit {
+            server_key: engine,
+            bid_bits: self.bid_bits,
+            units: self.units,
+            direction: self.direction,
+            pricing: self.pricing,
+            options,
+        };
+        multi_unit.run(&vec![
+            vec![engine.trivial(false); self.bid_bits];
+            bidder_count
+        ])?;
+        Ok(())
+    }
+}
+
+impl<'a, E: BooleanEngine> MultiUnit<'a, E> {
+    pub fn new(server_key: &'a E, bid_bits: usize, units: usize) -> Self {
+        MultiUnit {
+            server_key,
+            bid_bits,
+            units,
+            direction: Direction::default(),
+            pricing: MultiUnitPricing::default(),
+            options: CircuitOptions::default(),
+        }
+    }
+
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn with_pricing(mut self, pricing: MultiUnitPricing) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    pub fn units(&self) -> usize {
+        self.units
+    }
+
+    /// Runs multi-unit auction over `bids`, where `bids[j]` is bid of j^th bidder
+    pub fn run(
+        &self,
+        bids: &[Vec<E::Ciphertext>],
+    ) -> Result<MultiUnitResult<E::Ciphertext>, AuctionError> {
+        install(self.options.threads, || self.run_circuit(bids))
+    }
+
+    fn run_circuit(
+        &self,
+        bids: &[Vec<E::Ciphertext>],
+    ) -> Result<MultiUnitResult<E::Ciphertext>, AuctionError> {
+        validate_bids(bids, self.bid_bits, bids.len())?;
+        if self.units == 0 {
+            return Err(AuctionError::ZeroUnits);
+        }
+
+        let bidder_count = bids.len();
+        let (winners, clearing_price) = if self.units >= bidder_count {
+            // every bidder wins, which is public anyways
+            let clearing_price = vec![self.server_key.trivial(false); self.bid_bits];
+            (
+                vec![self.server_key.trivial(true); bidder_count],
+                self.direction.orient(self.server_key, &clearing_price),
+            )
+        } else {
+            let oriented = bids
+                .iter()
+                .map(|bid| self.direction.orient(self.server_key, bid))
+                .collect::<Vec<_>>();
+
+            // uniform price ranks one bidder more than units to find the clearing price
+            let slots = match self.pricing {
+                MultiUnitPricing::Uniform => self.units + 1,
+                MultiUnitPricing::PayAsBid => self.units,
+            };
+            let ranking = top_k_circuit(
+                self.server_key,
+                &oriented,
+                self.bid_bits,
+                slots,
+                self.options,
+            )?;
+
+            // one-hot vectors of distinct slots are disjoint, thus OR gives the winners
+            let mut winners = ranking.winners[0].clone();
+            for w in ranking.winners[1..self.units].iter() {
+                winners = try_map(&winners, |j, winner| self.server_key.or(winner, &w[j]))?;
+            }
+
+            let clearing_price = ranking
+                .amounts
+                .get(self.units)
+                .map(|amount| self.direction.orient(self.server_key, amount))
+                .unwrap_or_default();
+            (winners, clearing_price)
+        };
+
+        let payments = match self.pricing {
+            MultiUnitPricing::Uniform => Payments::Uniform(clearing_price),
+            MultiUnitPricing::PayAsBid => Payments::PayAsBid(
+                bids.iter()
+                    .zip(winners.iter())
+                    .map(|(bid, w_j)| mask(self.server_key, w_j, bid))
+                    .collect::<Result<_, _>>()?,
+            ),
+        };
+
+        Ok(MultiUnitResult { winners, payments })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+    use crate::test_utils::{decrypt_amount, decrypt_indices, encrypt_bid};
+
+    #[test]
+    fn uniform_price_auction_works() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let bids = [5, 9, 9, 3, 7];
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let encrypted_bids = bids
+            .iter()
+            .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        // (direction, units, expected winners, expected clearing price)
+        let cases = vec![
+            (Direction::Forward, 1, vec![1], 9),
+            (Direction::Forward, 2, vec![1, 2], 7),
+            (Direction::Forward, 3, vec![1, 2, 4], 5),
+            (Direction::Reverse, 2, vec![0, 3], 7),
+            (Direction::Forward, 5, vec![0, 1, 2, 3, 4], 0),
+            (Direction::Reverse, 6, vec![0, 1, 2, 3, 4], 255),
+        ];
+
+        for (direction, units, expected_winners, expected_price) in cases {
+            let res = MultiUnit::new(&server_key, bid_bits, units)
+                .with_direction(direction)
+                .run(&encrypted_bids)?;
+
+            assert_eq!(decrypt_indices(&client_key, &res.winners), expected_winners);
+            match res.payments {
+                Payments::Uniform(price) => {
+                    assert_eq!(decrypt_amount(&client_key, &price), expected_price)
+                }
+                Payments::PayAsBid(_) => panic!("expected uniform price"),
+            }
+        }
+
+        assert_eq!(
+            MultiUnit::new(&server_key, bid_bits, 0)
+                .run(&encrypted_bids)
+                .err(),
+            Some(AuctionError::ZeroUnits)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn pay_as_bid_auction_works() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let bids = [5, 9, 9, 3, 7];
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let encrypted_bids = bids
+            .iter()
+            .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        // (direction, units, expected payments per bidder)
+        let cases = vec![
+            (Direction::Forward, 3, vec![0, 9, 9, 0, 7]),
+            (Direction::Reverse, 2, vec![5, 0, 0, 3, 0]),
+            (Direction::Forward, 5, vec![5, 9, 9, 3, 7]),
+        ];
+
+        for (direction, units, expected_payments) in cases {
+            let res = MultiUnit::new(&server_key, bid_bits, units)
+                .with_direction(direction)
+                .with_pricing(MultiUnitPricing::PayAsBid)
+                .run(&encrypted_bids)?;
+
+            let expected_winners = (0..bids.len())
+                .filter(|j| expected_payments[*j] != 0)
+                .collect::<Vec<_>>();
+            assert_eq!(decrypt_indices(&client_key, &res.winners), expected_winners);
+            match res.payments {
+                Payments::PayAsBid(payments) => {
+                    let payments = payments
+                        .iter()
+                        .map(|p| decrypt_amount(&client_key, p))
+                        .collect::<Vec<_>>();
+                    assert_eq!(payments, expected_payments);
+                }
+                Payments::Uniform(_) => panic!("expected pay as bid"),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{AuctionError, BooleanEngine};
+
+/// How the multiplexer `b ? x : y` is evaluated.
+///
+/// Multiplexer updates `w` of every bidder in each bit iteration of bit-slice
+/// max, thus dominates the runtime of auction circuits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Multiplexer {
+    /// `(b & x) | (!b & y)` in 3-encoding. Costs 3 bootstraps with 2 bit plaintext
+    /// space.
+    #[default]
+    ThreeGate,
+    /// Single bootstrap in 7-encoding, supported by gadget server key via its
+    /// p-encoding multiplexer. Requires 3 bit plaintext space, thus the bootstrap is roughly
+    /// twice as expensive as in 3-encoding.
+    SevenEncoding,
+}
+
+/// Returns `x` if `b` else `y`
+pub(crate) fn mux<E: BooleanEngine>(
+    server_key: &E,
+    b: &E::Ciphertext,
+    x: &E::Ciphertext,
+    y: &E::Ciphertext,
+    multiplexer: Multiplexer,
+) -> Result<E::Ciphertext, AuctionError> {
+    match multiplexer {
+        Multiplexer::ThreeGate => {
+            let c0 = server_key.and(b, x)?;
+            let c1 = server_key.and(&server_key.not(b), y)?;
+            server_key.or(&c0, &c1)
+        }
+        Multiplexer::SevenEncoding => server_key.seven_encoding_mux(b, x, y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+
+    #[test]
+    fn multiplexers_work() -> Result<(), Box<dyn std::error::Error>> {
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+
+        let multiplexers = [Multiplexer::ThreeGate, Multiplexer::SevenEncoding];
+        for multiplexer in multiplexers {
+            for m in 0..8 {
+                let (b, x, y) = (m & 4 != 0, m & 2 != 0, m & 1 != 0);
+                let res = mux(
+                    &server_key,
+                    &client_key.encrypt(b),
+                    &client_key.encrypt(x),
+                    &client_key.encrypt(y),
+                    multiplexer,
+                )?;
+                assert_eq!(client_key.decrypt(&res), if b { x } else { y });
+            }
+        }
+
+        // upstream boolean server key has no p-encoding multiplexer
+        let (client_key, server_key) = tfhe::boolean::gen_keys();
+        let ct = client_key.encrypt(true);
+        assert_eq!(
+            mux(&server_key, &ct, &ct, &ct, Multiplexer::SevenEncoding).err(),
+            Some(AuctionError::Unsupported(
+                "engine does not support 7-encoding multiplexer"
+            ))
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::AuctionError;
+
+/// Maps `f` over `items`, where `f` receives index of the item.
+///
+/// Per bidder gates are independent of each other, thus with `parallel` feature
+/// they are evaluated on rayon's thread pool. Otherwise items are mapped in
+/// order.
+pub(crate) fn try_map<T, U, F>(items: &[T], f: F) -> Result<Vec<U>, AuctionError>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(usize, &T) -> Result<U, AuctionError> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().enumerate().map(|(j, t)| f(j, t)).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().enumerate().map(|(j, t)| f(j, t)).collect()
+    }
+}
+
+/// Runs `f` on a thread pool with `threads` threads. If `threads` is not set `f`
+/// runs on rayon's global thread pool.
+#[cfg(feature = "parallel")]
+pub(crate) fn install<R, F>(threads: Option<usize>, f: F) -> Result<R, AuctionError>
+where
+    R: Send,
+    F: FnOnce() -> Result<R, AuctionError> + Send,
+{
+    match threads {
+        Some(threads) => rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(|e| AuctionError::ThreadPool(e.to_string()))?
+            .install(f),
+        None => f(),
+    }
+}
+
+/// Runs `f` on the current thread
+#[cfg(not(feature = "parallel"))]
+pub(crate) fn install<R, F>(_threads: Option<usize>, f: F) -> Result<R, AuctionError>
+where
+    F: FnOnce() -> Result<R, AuctionError>,
+{
+    f()
+}
@@ -0,0 +1,86 @@
+use crate::{parallel::try_map, AuctionError, BooleanEngine};
+
+/// How OR of `s[0..n]` is evaluated in each bit iteration of bit-slice max.
+///
+/// Both evaluate `n - 1` OR gates and produce the same plaintext result, they
+/// only differ in depth, i.e. no. of sequential bootstraps on the critical path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrReduction {
+    /// Linear chain `((s[0] | s[1]) | s[2]) | ...` with depth `n - 1`
+    Chain,
+    /// Balanced binary tree with depth `ceil(log2(n))`. Gates within a level are
+    /// independent and evaluated in parallel with `parallel` feature, otherwise
+    /// latency equals the chain.
+    #[default]
+    Tree,
+}
+
+impl OrReduction {
+    /// Returns no. of sequential OR gates to reduce `n` bits
+    pub fn depth(self, n: usize) -> usize {
+        match self {
+            OrReduction::Chain => n.saturating_sub(1),
+            OrReduction::Tree => n.max(1).next_power_of_two().trailing_zeros() as usize,
+        }
+    }
+}
+
+/// Returns OR of `bits`. `bits` must be non-empty.
+pub(crate) fn or_reduce<E: BooleanEngine>(
+    server_key: &E,
+    bits: Vec<E::Ciphertext>,
+    reduction: OrReduction,
+) -> Result<E::Ciphertext, AuctionError> {
+    match reduction {
+        OrReduction::Chain => {
+            let mut bits = bits.into_iter();
+            let mut b = bits.next().expect("bits must be non-empty");
+            for bit in bits {
+                b = server_key.or(&b, &bit)?;
+            }
+            Ok(b)
+        }
+        OrReduction::Tree => {
+            let mut level = bits;
+            while level.len() > 1 {
+                let pairs = level.chunks(2).collect::<Vec<_>>();
+                level = try_map(&pairs, |_, pair| match pair {
+                    [x, y] => server_key.or(x, y),
+                    // odd one out is carried to the next level
+                    _ => Ok(pair[0].clone()),
+                })?;
+            }
+            Ok(level.pop().expect("bits must be non-empty"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+
+    #[test]
+    fn or_reductions_agree() -> Result<(), Box<dyn std::error::Error>> {
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+
+        for n in 1..=5 {
+            for m in 0..(1u32 << n) {
+                let bits = (0..n)
+                    .map(|j| client_key.encrypt((m >> j) & 1 == 1))
+                    .collect::<Vec<_>>();
+                let chain = or_reduce(&server_key, bits.clone(), OrReduction::Chain)?;
+                let tree = or_reduce(&server_key, bits, OrReduction::Tree)?;
+                assert_eq!(client_key.decrypt(&chain), m != 0);
+                assert_eq!(client_key.decrypt(&tree), m != 0);
+            }
+        }
+
+        assert_eq!(OrReduction::Chain.depth(50), 49);
+        assert_eq!(OrReduction::Tree.depth(50), 6);
+        assert_eq!(OrReduction::Tree.depth(1), 0);
+
+        Ok(())
+    }
+}
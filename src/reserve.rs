@@ -0,0 +1,69 @@
+use crate::{
+    mux::{mux, Multiplexer},
+    AuctionError, BooleanEngine,
+};
+
+/// Returns encryption of 1 iff `x >= y`, where `x` and `y` are bits from MSB to LSB.
+///
+/// Walks from LSB to MSB maintaining `ge = x[i..] >= y[i..]`. Since `x >= y` iff
+/// `x + !y + 1` carries out, `ge` is the carry of the ripple adder, i.e.
+/// `maj(x_i, !y_i, ge)`. Costs 4 gates per bit.
+pub(crate) fn greater_or_equal<E: BooleanEngine>(
+    server_key: &E,
+    x: &[E::Ciphertext],
+    y: &[E::Ciphertext],
+) -> Result<E::Ciphertext, AuctionError> {
+    let mut ge = server_key.trivial(true);
+    for (x_i, y_i) in x.iter().zip(y.iter()).rev() {
+        let y_i_not = server_key.not(y_i);
+        // maj(a, b, c) = (a & b) | (c & (a | b))
+        let c0 = server_key.and(x_i, &y_i_not)?;
+        let c1 = server_key.or(x_i, &y_i_not)?;
+        let c1 = server_key.and(&ge, &c1)?;
+        ge = server_key.or(&c0, &c1)?;
+    }
+    Ok(ge)
+}
+
+/// Returns bits of `max(x, y)`
+pub(crate) fn max<E: BooleanEngine>(
+    server_key: &E,
+    x: &[E::Ciphertext],
+    y: &[E::Ciphertext],
+    multiplexer: Multiplexer,
+) -> Result<Vec<E::Ciphertext>, AuctionError> {
+    let ge = greater_or_equal(server_key, x, y)?;
+    x.iter()
+        .zip(y.iter())
+        .map(|(x_i, y_i)| mux(server_key, &ge, x_i, y_i, multiplexer))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+    use crate::test_utils::{decrypt_amount, encrypt_bid};
+
+    #[test]
+    fn greater_or_equal_and_max_work() -> Result<(), Box<dyn std::error::Error>> {
+        let bits = 2;
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+
+        for x in 0..4u64 {
+            for y in 0..4u64 {
+                let x_ct = encrypt_bid(&client_key, x, bits);
+                let y_ct = encrypt_bid(&client_key, y, bits);
+
+                let ge = greater_or_equal(&server_key, &x_ct, &y_ct)?;
+                assert_eq!(client_key.decrypt(&ge), x >= y, "{x} >= {y}");
+
+                let m = max(&server_key, &x_ct, &y_ct, Multiplexer::default())?;
+                assert_eq!(decrypt_amount(&client_key, &m), x.max(y));
+            }
+        }
+
+        Ok(())
+    }
+}
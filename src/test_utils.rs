@@ -0,0 +1,35 @@
+use tfhe::gadget::{ciphertext::Ciphertext, client_key::ClientKey};
+
+/// Encrypts `amount` as `bid_bits` bits from MSB to LSB
+pub(crate) fn encrypt_bid(client_key: &ClientKey, amount: u64, bid_bits: usize) -> Vec<Ciphertext> {
+    (0..bid_bits)
+        .map(|i| client_key.encrypt((amount >> (bid_bits - 1 - i)) & 1 != 0))
+        .collect()
+}
+
+/// Decrypts amount bits stored from MSB to LSB
+pub(crate) fn decrypt_amount(client_key: &ClientKey, bits: &[Ciphertext]) -> u64 {
+    bits.iter()
+        .fold(0u64, |acc, ct| (acc << 1) | client_key.decrypt(ct) as u64)
+}
+
+/// Returns `amount` as `bid_bits` plaintext bits from MSB to LSB
+pub(crate) fn to_bits(amount: u64, bid_bits: usize) -> Vec<bool> {
+    (0..bid_bits)
+        .map(|i| (amount >> (bid_bits - 1 - i)) & 1 != 0)
+        .collect()
+}
+
+/// Returns amount of plaintext bits stored from MSB to LSB
+pub(crate) fn from_bits(bits: &[bool]) -> u64 {
+    bits.iter().fold(0u64, |acc, bit| (acc << 1) | *bit as u64)
+}
+
+/// Decrypts indicator vector and returns indices set to 1
+pub(crate) fn decrypt_indices(client_key: &ClientKey, bits: &[Ciphertext]) -> Vec<usize> {
+    bits.iter()
+        .enumerate()
+        .filter(|(_, ct)| client_key.decrypt(ct))
+        .map(|(index, _)| index)
+        .collect()
+}
@@ -0,0 +1,86 @@
+use crate::{AuctionError, BooleanEngine};
+
+/// Policy to reduce tied winners to a single winner
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Tied bidder with the lowest index wins
+    LowestIndex,
+    /// Tied bidder appearing first in the order wins. Order must be a permutation
+    /// of bidder indices, supplied by the auctioneer.
+    Priority(Vec<usize>),
+}
+
+impl TieBreak {
+    /// Returns bidder indices from the highest priority to the lowest
+    fn order(&self, bidder_count: usize) -> Result<Vec<usize>, AuctionError> {
+        match self {
+            TieBreak::LowestIndex => Ok((0..bidder_count).collect()),
+            TieBreak::Priority(order) => {
+                let mut seen = vec![false; bidder_count];
+                if order.len() != bidder_count {
+                    return Err(AuctionError::InvalidPriority);
+                }
+                for &j in order {
+                    if j >= bidder_count || seen[j] {
+                        return Err(AuctionError::InvalidPriority);
+                    }
+                    seen[j] = true;
+                }
+                Ok(order.clone())
+            }
+        }
+    }
+}
+
+/// Reduces indicator vector `w` to a one-hot vector as per `policy`.
+///
+/// Scans bidders in priority order keeping `seen = 1` once a winner is found, so
+/// that only the first winner survives. Costs 2 gates per bidder. If `w` is all
+/// zero the output is all zero.
+pub(crate) fn tie_break<E: BooleanEngine>(
+    server_key: &E,
+    w: &[E::Ciphertext],
+    policy: &TieBreak,
+) -> Result<Vec<E::Ciphertext>, AuctionError> {
+    let mut out = w.to_vec();
+    let mut seen = server_key.trivial(false);
+    for j in policy.order(w.len())? {
+        out[j] = server_key.and(&w[j], &server_key.not(&seen))?;
+        seen = server_key.or(&seen, &w[j])?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+    use crate::test_utils::decrypt_indices;
+
+    #[test]
+    fn tie_break_works() -> Result<(), Box<dyn std::error::Error>> {
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let w = [false, true, false, true, true]
+            .iter()
+            .map(|b| client_key.encrypt(*b))
+            .collect::<Vec<_>>();
+
+        let res = tie_break(&server_key, &w, &TieBreak::LowestIndex)?;
+        assert_eq!(decrypt_indices(&client_key, &res), vec![1]);
+
+        let res = tie_break(&server_key, &w, &TieBreak::Priority(vec![0, 4, 2, 1, 3]))?;
+        assert_eq!(decrypt_indices(&client_key, &res), vec![4]);
+
+        assert_eq!(
+            tie_break(&server_key, &w, &TieBreak::Priority(vec![0, 1, 2, 3])).err(),
+            Some(AuctionError::InvalidPriority)
+        );
+        assert_eq!(
+            tie_break(&server_key, &w, &TieBreak::Priority(vec![0, 1, 2, 3, 3])).err(),
+            Some(AuctionError::InvalidPriority)
+        );
+
+        Ok(())
+    }
+}
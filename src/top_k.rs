@@ -0,0 +1,216 @@
+use tfhe::gadget::{ciphertext::Ciphertext, server_key::ServerKey};
+
+use crate::{
+    bit_slice_max,
+    cost::CountingEngine,
+    parallel::{install, try_map},
+    tie_break::{tie_break, TieBreak},
+    validate_bids, AuctionError, BooleanEngine, CircuitBuilder, CircuitOptions, Direction,
+    PlaintextEngine,
+};
+
+/// Ranks the `slots` best bids over encrypted bids.
+///
+/// Each slot runs bit-slice max over the remaining bidders followed by tie
+/// break, and all slots but the last mask out the selected bidder. Thus top-k
+/// costs roughly `slots` first price auctions.
+///
+/// Each bid must be `bid_bits` encrypted bits ordered from MSB to LSB.
+pub struct TopK<'a, E: BooleanEngine = ServerKey> {
+    server_key: &'a E,
+    bid_bits: usize,
+    slots: usize,
+    direction: Direction,
+    options: CircuitOptions,
+}
+
+/// Encrypted output of top-k ranking, best slot first
+#[derive(Clone)]
+pub struct TopKResult<C = Ciphertext> {
+    /// `winners[r]` is a one-hot vector encrypting 1 at the bidder ranked r^th
+    pub winners: Vec<Vec<C>>,
+    /// `amounts[r]` is the bid of bidder ranked r^th, bits from MSB to LSB
+    pub amounts: Vec<Vec<C>>,
+}
+
+impl<'a, E: BooleanEngine> CircuitBuilder for TopK<'a, E> {
+    fn options(&self) -> &CircuitOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut CircuitOptions {
+        &mut self.options
+    }
+
+    fn count_gates(
+        &self,
+        engine: &CountingEngine<PlaintextEngine>,
+        bidder_count: usize,
+        options: CircuitOptions,
+    ) -> Result<(), AuctionError> {
+        let top_k = TopK {
+            server_key: engine,
+            bid_bits: self.bid_bits,
+            slots: self.slots,
+            direction: self.direction,
+            options,
+        };
+        top_k.run(&vec![
+            vec![engine.trivial(false); self.bid_bits];
+            bidder_count
+        ])?;
+        Ok(())
+    }
+}
+
+impl<'a, E: BooleanEngine> TopK<'a, E> {
+    pub fn new(server_key: &'a E, bid_bits: usize, slots: usize) -> Self {
+        TopK {
+            server_key,
+            bid_bits,
+            slots,
+            direction: Direction::default(),
+            options: CircuitOptions::default(),
+        }
+    }
+
+    pub fn with_direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Runs top-k circuit over `bids`, where `bids[j]` is bid of j^th bidder
+    pub fn run(
+        &self,
+        bids: &[Vec<E::Ciphertext>],
+    ) -> Result<TopKResult<E::Ciphertext>, AuctionError> {
+        install(self.options.threads, || self.run_circuit(bids))
+    }
+
+    fn run_circuit(
+        &self,
+        bids: &[Vec<E::Ciphertext>],
+    ) -> Result<TopKResult<E::Ciphertext>, AuctionError> {
+        validate_bids(bids, self.bid_bits, bids.len())?;
+        let bids = bids
+            .iter()
+            .map(|bid| self.direction.orient(self.server_key, bid))
+            .collect::<Vec<_>>();
+
+        let mut res = top_k_circuit(
+            self.server_key,
+            &bids,
+            self.bid_bits,
+            self.slots,
+            self.options,
+        )?;
+        for amount in res.amounts.iter_mut() {
+            *amount = self.direction.orient(self.server_key, amount);
+        }
+
+        Ok(res)
+    }
+}
+
+/// Top-k circuit. Returns one-hot indicator vectors and amounts of the `slots`
+/// highest bids, highest first.
+///
+/// Repeats bit-slice max `slots` times. After each round ties are broken
+/// towards the lowest index and the selected bidder is masked out of the next
+/// round, thus tied bids occupy consecutive slots.
+pub(crate) fn top_k_circuit<E: BooleanEngine>(
+    server_key: &E,
+    bids: &[Vec<E::Ciphertext>],
+    bid_bits: usize,
+    slots: usize,
+    options: CircuitOptions,
+) -> Result<TopKResult<E::Ciphertext>, AuctionError> {
+    let bidder_count = bids.len();
+    if slots == 0 || slots > bidder_count {
+        return Err(AuctionError::InvalidSlots {
+            slots,
+            bidder_count,
+        });
+    }
+
+    let mut active = vec![server_key.trivial(true); bidder_count];
+    let mut winners = Vec::with_capacity(slots);
+    let mut amounts = Vec::with_capacity(slots);
+    for r in 0..slots {
+        let (w, amount) = bit_slice_max(server_key, bids, bid_bits, active.clone(), options)?;
+        let w = tie_break(server_key, &w, &TieBreak::LowestIndex)?;
+
+        if r + 1 < slots {
+            active = try_map(&active, |j, a| server_key.and(a, &server_key.not(&w[j])))?;
+        }
+
+        winners.push(w);
+        amounts.push(amount);
+    }
+
+    Ok(TopKResult { winners, amounts })
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+    use crate::test_utils::{decrypt_amount, decrypt_indices, encrypt_bid};
+
+    #[test]
+    fn top_k_works() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let bids = [5, 9, 9, 3, 7];
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+        let encrypted_bids = bids
+            .iter()
+            .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+            .collect::<Vec<_>>();
+
+        // (direction, slots, expected (bidder, amount) per slot)
+        let cases = vec![
+            (Direction::Forward, 3, vec![(1, 9), (2, 9), (4, 7)]),
+            (Direction::Reverse, 2, vec![(3, 3), (0, 5)]),
+            (
+                Direction::Forward,
+                5,
+                vec![(1, 9), (2, 9), (4, 7), (0, 5), (3, 3)],
+            ),
+        ];
+
+        for (direction, slots, expected) in cases {
+            let res = TopK::new(&server_key, bid_bits, slots)
+                .with_direction(direction)
+                .run(&encrypted_bids)?;
+
+            let ranking = res
+                .winners
+                .iter()
+                .zip(res.amounts.iter())
+                .map(|(w, amount)| {
+                    let w = decrypt_indices(&client_key, w);
+                    assert_eq!(w.len(), 1);
+                    (w[0], decrypt_amount(&client_key, amount))
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(ranking, expected);
+        }
+
+        assert_eq!(
+            TopK::new(&server_key, bid_bits, 6)
+                .run(&encrypted_bids)
+                .err(),
+            Some(AuctionError::InvalidSlots {
+                slots: 6,
+                bidder_count: 5
+            })
+        );
+
+        Ok(())
+    }
+}
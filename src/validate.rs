@@ -0,0 +1,99 @@
+use crate::{AuctionError, BidBit};
+
+/// Checks a single bid submitted by `bidder` is well formed.
+///
+/// Bid must consist of exactly `bid_bits` ciphertexts and each of them must be a
+/// proper encryption as per [BidBit::validate], for ex. gadget ciphertexts cannot
+/// be placeholders or trivial ciphertexts.
+pub fn validate_bid<C: BidBit>(
+    bidder: usize,
+    bid: &[C],
+    bid_bits: usize,
+) -> Result<(), AuctionError> {
+    if bid.len() != bid_bits {
+        return Err(AuctionError::BidLength {
+            bidder,
+            expected: bid_bits,
+            found: bid.len(),
+        });
+    }
+
+    bid.iter()
+        .enumerate()
+        .try_for_each(|(bit, ct)| ct.validate(bidder, bit))
+}
+
+/// Checks `bids` are well formed for an auction with `bidder_count` bidders and
+/// `bid_bits` bits per bid.
+pub fn validate_bids<C: BidBit>(
+    bids: &[Vec<C>],
+    bid_bits: usize,
+    bidder_count: usize,
+) -> Result<(), AuctionError> {
+    if bid_bits == 0 {
+        return Err(AuctionError::ZeroBidBits);
+    }
+    if bidder_count == 0 {
+        return Err(AuctionError::NoBidders);
+    }
+    if bids.len() != bidder_count {
+        return Err(AuctionError::BidderCount {
+            expected: bidder_count,
+            found: bids.len(),
+        });
+    }
+
+    bids.iter()
+        .enumerate()
+        .try_for_each(|(bidder, bid)| validate_bid(bidder, bid, bid_bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, ciphertext::Ciphertext, gen_keys};
+
+    use super::*;
+
+    #[test]
+    fn validate_bids_reports_bad_bidder_and_bit() {
+        let (client_key, _) = gen_keys(&BOOLEAN_PARAMETERS);
+        let bid = || {
+            (0..4)
+                .map(|_| client_key.encrypt(false))
+                .collect::<Vec<_>>()
+        };
+
+        let mut bids = vec![bid(), bid(), bid()];
+        assert_eq!(validate_bids(&bids, 4, 3), Ok(()));
+        assert_eq!(
+            validate_bids(&bids, 4, 2),
+            Err(AuctionError::BidderCount {
+                expected: 2,
+                found: 3
+            })
+        );
+        assert_eq!(validate_bids(&bids, 0, 3), Err(AuctionError::ZeroBidBits));
+
+        bids[2][3] = Ciphertext::Trivial(true);
+        assert_eq!(
+            validate_bids(&bids, 4, 3),
+            Err(AuctionError::TrivialBit { bidder: 2, bit: 3 })
+        );
+
+        bids[1][1] = Ciphertext::Placeholder;
+        assert_eq!(
+            validate_bids(&bids, 4, 3),
+            Err(AuctionError::PlaceholderBit { bidder: 1, bit: 1 })
+        );
+
+        bids[0].pop();
+        assert_eq!(
+            validate_bids(&bids, 4, 3),
+            Err(AuctionError::BidLength {
+                bidder: 0,
+                expected: 4,
+                found: 3
+            })
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{bit_slice_max, mux::mux, validate_bids, AuctionError, BooleanEngine, CircuitOptions};
+
+/// Output of [second_price_circuit], amounts as bits from MSB to LSB
+pub(crate) struct SecondPriceOutput<C> {
+    /// Indicator vector of highest bidders
+    pub(crate) winners: Vec<C>,
+    pub(crate) highest: Vec<C>,
+    /// Amount paid by the winner
+    pub(crate) price: Vec<C>,
+}
+
+/// Second price (Vickrey) auction circuit.
+/// If the highest bid is placed by more than one bidder
+/// the price equals the highest bid. With a single bidder the price is 0.
+///
+/// Second highest amount is obtained by running bit-slice max a second time
+/// with the highest bidders masked out. Costs roughly twice the first price
+/// circuit plus `3n` gates to detect ties and `3k` gates for the final mux.
+pub(crate) fn second_price_circuit<E: BooleanEngine>(
+    server_key: &E,
+    bids: &[Vec<E::Ciphertext>],
+    bid_bits: usize,
+    bidder_count: usize,
+    options: CircuitOptions,
+) -> Result<SecondPriceOutput<E::Ciphertext>, AuctionError> {
+    validate_bids(bids, bid_bits, bidder_count)?;
+
+    let (w, highest) = bit_slice_max(
+        server_key,
+        bids,
+        bid_bits,
+        vec![server_key.trivial(true); bidder_count],
+        options,
+    )?;
+
+    // highest bid among bidders that did not place the highest bid
+    let losers = w.iter().map(|w_j| server_key.not(w_j)).collect();
+    let (_, runner_up) = bit_slice_max(server_key, bids, bid_bits, losers, options)?;
+
+    let tie = at_least_two(server_key, &w)?;
+    let price = highest
+        .iter()
+        .zip(runner_up.iter())
+        .map(|(h, r)| mux(server_key, &tie, h, r, options.multiplexer))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(SecondPriceOutput {
+        winners: w,
+        highest,
+        price,
+    })
+}
+
+/// Returns encryption of 1 iff at least two bits in `w` are set
+pub(crate) fn at_least_two<E: BooleanEngine>(
+    server_key: &E,
+    w: &[E::Ciphertext],
+) -> Result<E::Ciphertext, AuctionError> {
+    // `any` is set once a bit is seen, `two` once a bit is seen with `any` already set
+    let mut any = server_key.trivial(false);
+    let mut two = server_key.trivial(false);
+    for w_j in w {
+        let c = server_key.and(&any, w_j)?;
+        two = server_key.or(&two, &c)?;
+        any = server_key.or(&any, w_j)?;
+    }
+    Ok(two)
+}
+
+#[cfg(test)]
+mod tests {
+    use tfhe::gadget::{boolean::BOOLEAN_PARAMETERS, gen_keys};
+
+    use super::*;
+    use crate::test_utils::{decrypt_amount, decrypt_indices, encrypt_bid};
+
+    #[test]
+    fn second_price_circuit_works() -> Result<(), Box<dyn std::error::Error>> {
+        let bid_bits = 8;
+        let (client_key, server_key) = gen_keys(&BOOLEAN_PARAMETERS);
+
+        // (bids, expected winners, expected price)
+        let cases: Vec<(Vec<u64>, Vec<usize>, u64)> = vec![
+            (vec![17, 200, 45, 199], vec![1], 199),
+            (vec![200, 3, 200, 150], vec![0, 2], 200),
+            (vec![0, 0, 0], vec![0, 1, 2], 0),
+            (vec![91], vec![0], 0),
+        ];
+
+        for (bids, expected_winners, expected_price) in cases {
+            let encrypted_bids = bids
+                .iter()
+                .map(|bid| encrypt_bid(&client_key, *bid, bid_bits))
+                .collect::<Vec<_>>();
+            let SecondPriceOutput {
+                winners,
+                highest,
+                price,
+            } = second_price_circuit(
+                &server_key,
+                &encrypted_bids,
+                bid_bits,
+                bids.len(),
+                CircuitOptions::default(),
+            )?;
+
+            assert_eq!(decrypt_indices(&client_key, &winners), expected_winners);
+            assert_eq!(
+                decrypt_amount(&client_key, &highest),
+                *bids.iter().max().unwrap()
+            );
+            assert_eq!(decrypt_amount(&client_key, &price), expected_price);
+        }
+
+        Ok(())
+    }
+}